@@ -43,3 +43,16 @@ pub async fn check(file: &mut File, sha: &str) -> Result<bool, IntegrityCheckErr
 
 	Ok(hex.iter().zip(&bytes).all(|(a, b)| a == b))
 }
+
+/// Hex encoded sha1 of an in-memory body
+pub fn sha1_hex(bytes: &[u8]) -> String {
+	Sha1::digest(bytes)
+		.iter()
+		.map(|it| format!("{it:02x}"))
+		.collect()
+}
+
+/// Checks an in-memory body against a hex encoded sha1
+pub fn check_bytes(bytes: &[u8], sha: &str) -> bool {
+	sha1_hex(bytes).eq_ignore_ascii_case(sha)
+}
@@ -1,31 +1,28 @@
 use {
-	launchers::{
-		distros::{
-			mojang::{
-				Mojang,
-				VersionOptions,
-			},
-			Distro,
-		},
+	crate::lookup::version_index,
+	download::{
+		download_all,
+		DownloadEvent,
+		Item,
+	},
+	reqwest::Client,
+	rsmc::{
 		install::{
-			executor::{
-				ActionExecutorExt,
-				DownloadEvent,
-			},
-			Install,
+			InstallPlanner,
+			JavaRuntimeInstall,
+			JavaRuntimeInstaller,
+		},
+		spec::{
+			resolve_manifest,
+			NativeManifest,
+			RuleContext,
 		},
 	},
 	std::{
-		collections::HashMap,
-		io::{
-			BufRead,
-			BufReader,
-		},
 		path::{
 			Path,
 			PathBuf,
 		},
-		process::Stdio,
 		sync::Arc,
 		time::{
 			Duration,
@@ -36,33 +33,75 @@ use {
 		Manager,
 		Window,
 	},
-	tokio::sync::mpsc,
+	tokio::{
+		fs,
+		sync::mpsc,
+	},
 	tokio_util::sync::CancellationToken,
+	tracing::{
+		info,
+		warn,
+	},
 };
-use launchers::launch::TryIntoLauncher;
 
-#[tauri::command]
-pub async fn mojang_prepare(
-	window: Window,
-	version: VersionOptions,
-	path: PathBuf,
-) -> Result<(), String> {
-	let distro = Mojang::try_from_root_else_install(&path, &version)
+async fn install_manifest(path: &Path, id: &str) -> Result<(), String> {
+	let manifest_path = path.join("versions").join(id).join(format!("{id}.json"));
+
+	if fs::try_exists(&manifest_path)
 		.await
-		.map_err(|it| it.to_string())?;
+		.map_err(|it| it.to_string())?
+	{
+		return Ok(());
+	}
+
+	let index = version_index().get().await.map_err(|it| it.to_string())?;
+	let entry = index
+		.find(id)
+		.ok_or_else(|| format!("Unknown version: {id}"))?;
+
+	version_index()
+		.install_manifest(path, entry)
+		.await
+		.map_err(|it| it.to_string())
+}
+
+async fn prepare_runtime(
+	client: &Client,
+	path: &Path,
+	manifest: &NativeManifest,
+	ctx: &RuleContext,
+) -> Option<JavaRuntimeInstall> {
+	match JavaRuntimeInstaller::new(client.clone())
+		.prepare(path, &manifest.java_version.component, ctx)
+		.await
+	{
+		Ok(runtime) => Some(runtime),
+		Err(err) => {
+			warn!(?err, "Java runtime is not available for this platform");
+			None
+		}
+	}
+}
+
+async fn download(
+	window: &Window,
+	client: Client,
+	mut items: Vec<Item>,
+	runtime: Option<JavaRuntimeInstall>,
+) -> Result<(), String> {
+	if let Some(runtime) = &runtime {
+		items.extend(runtime.items.iter().cloned());
+	}
 
-	let actions = distro.install().await.map_err(|it| it.to_string())?;
+	info!("Downloading {} items", items.len());
 
 	let (tx, mut rx) = mpsc::channel(1024);
 	let token = Arc::new(CancellationToken::new());
 
 	let task_token = token.clone();
 
-	let task = {
-		let path = path.clone();
-
-		tokio::spawn(actions.execute(path, Arc::new(tx), task_token))
-	};
+	let task =
+		tokio::spawn(async move { download_all(&client, items, Arc::new(tx), task_token, 16).await });
 
 	let now = SystemTime::now();
 	let mut next = SystemTime::now().elapsed().unwrap();
@@ -87,80 +126,60 @@ pub async fn mojang_prepare(
 		.map_err(|it| it.to_string())?
 		.map_err(|it| it.to_string())?;
 
+	if let Some(runtime) = runtime {
+		runtime.finalize().await.map_err(|it| it.to_string())?;
+	}
+
 	Ok(())
 }
 
 #[tauri::command]
-pub async fn mojang_launch(
-	window: Window,
-	uid: String,
-	id: &str,
-	path: &Path,
-	vars: HashMap<String, String>,
-) -> Result<(), String> {
-	let distro = Mojang::try_from_root(path, id)
+pub async fn mojang_prepare(window: Window, id: String, path: PathBuf) -> Result<(), String> {
+	install_manifest(&path, &id).await?;
+
+	let manifest = resolve_manifest(&path, &id)
 		.await
 		.map_err(|it| it.to_string())?;
 
-	let mut launcher = distro.try_into_launcher().map_err(|it| it.to_string())?;
+	let ctx = RuleContext::target().map_err(|it| it.to_string())?;
 
-	launcher.cwd = path.to_owned();
-	launcher.vars.extend(vars);
+	let client = Client::new();
 
-	let mut command = launcher.into_command();
-
-	command.stdout(Stdio::piped());
-	command.stderr(Stdio::piped());
-
-	let process = command.spawn().map_err(|it| it.to_string())?;
+	let items = InstallPlanner::new(client.clone())
+		.plan(&path, &manifest, &ctx)
+		.await
+		.map_err(|it| it.to_string())?;
 
-	let window = Arc::new(window);
+	let runtime = prepare_runtime(&client, &path, &manifest, &ctx).await;
 
-	let stdout_task = tokio::spawn({
-		let window = window.clone();
-		let event_name = format!("log::{uid}");
-		let pipe = process.stdout.unwrap();
-		let mut buf = BufReader::new(pipe);
+	info!("Installing {id}");
 
-		async move {
-			loop {
-				let mut out = String::new();
+	download(&window, client, items, runtime).await
+}
 
-				if let Ok(bytes) = buf.read_line(&mut out) {
-					let _ = window.emit(&event_name, &out);
+#[tauri::command]
+pub async fn mojang_prepare_server(
+	window: Window,
+	id: String,
+	path: PathBuf,
+) -> Result<(), String> {
+	install_manifest(&path, &id).await?;
 
-					if bytes == 0 && out.is_empty() {
-						break;
-					}
-				}
-			}
-		}
-	});
+	let manifest = resolve_manifest(&path, &id)
+		.await
+		.map_err(|it| it.to_string())?;
 
-	let stderr_task = tokio::spawn({
-		let window = window.clone();
-		let event_name = format!("log::{uid}");
-		let pipe = process.stderr.unwrap();
-		let mut buf = BufReader::new(pipe);
+	let ctx = RuleContext::target().map_err(|it| it.to_string())?;
 
-		async move {
-			loop {
-				let mut out = String::new();
+	let client = Client::new();
 
-				if let Ok(bytes) = buf.read_line(&mut out) {
-					let _ = window.emit(&event_name, &out);
+	let items = InstallPlanner::new(client.clone())
+		.plan_server(&path, &manifest)
+		.map_err(|it| it.to_string())?;
 
-					if bytes == 0 && out.is_empty() {
-						break;
-					}
-				}
-			}
-		}
-	});
+	let runtime = prepare_runtime(&client, &path, &manifest, &ctx).await;
 
-	let (a, b) = tokio::join!(stdout_task, stderr_task);
-	a.map_err(|it| it.to_string())?;
-	b.map_err(|it| it.to_string())?;
+	info!("Installing server {id}");
 
-	Ok(())
+	download(&window, client, items, runtime).await
 }
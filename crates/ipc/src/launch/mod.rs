@@ -1,21 +1,182 @@
 use {
-	rsmc::launcher::Launcher,
+	rsmc::{
+		launcher::{
+			parse_output,
+			JvmOptions,
+			LaunchTarget,
+			Launcher,
+			SECRET_PLACEHOLDERS,
+		},
+		spec::{
+			validate_manifest,
+			Diagnostic,
+		},
+	},
+	serde::Deserialize,
 	std::{
 		collections::HashMap,
-		path::PathBuf,
+		path::{
+			Path,
+			PathBuf,
+		},
+		sync::OnceLock,
 	},
 	tauri::{
 		AppHandle,
 		Manager,
 		Window,
 	},
+	tokio::sync::{
+		mpsc::{
+			self,
+			Sender,
+		},
+		Mutex,
+	},
 	tracing::{
 		error,
 		info,
 		trace,
+		warn,
 	},
 };
 
+mod server;
+
+pub use server::*;
+
+/// Stop requests of running games by their pids, `true` kills the game
+static STOPS: OnceLock<Mutex<HashMap<u32, Sender<bool>>>> = OnceLock::new();
+
+fn stops() -> &'static Mutex<HashMap<u32, Sender<bool>>> {
+	STOPS.get_or_init(Default::default)
+}
+
+#[tauri::command]
+pub async fn validate_version(root: PathBuf, id: String) -> Result<Vec<Diagnostic>, String> {
+	validate_manifest(&root, &id)
+		.await
+		.map_err(|it| it.to_string())
+}
+
+/// Settings of a launch beyond the placeholder values
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LaunchOptions {
+	pub jvm: Option<JvmOptions>,
+	pub target: Option<LaunchTarget>,
+	pub working_directory: Option<PathBuf>,
+	pub env: HashMap<String, String>,
+	pub removed_env: Vec<String>,
+	/// Command prefix, e.g. `["gamemoderun"]`
+	pub wrapper: Vec<String>,
+}
+
+async fn build_launcher(
+	root: &Path,
+	id: &str,
+	mut vars: HashMap<String, String>,
+	options: LaunchOptions,
+) -> Result<Launcher, String> {
+	let LaunchOptions {
+		jvm,
+		target,
+		working_directory,
+		env,
+		removed_env,
+		wrapper,
+	} = options;
+
+	let resolution = vars
+		.remove("resolution_width")
+		.zip(vars.remove("resolution_height"))
+		.and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+
+	let builder = vars
+		.into_iter()
+		.fold(Launcher::builder(root, id), |builder, (name, value)| {
+			builder.with_var(name, value)
+		});
+
+	let builder = match resolution {
+		Some((width, height)) => builder.with_resolution(width, height),
+		None => builder,
+	};
+
+	let builder = match jvm {
+		Some(jvm) => builder.with_jvm_options(jvm),
+		None => builder,
+	};
+
+	let builder = match target {
+		Some(target) => builder.with_target(target),
+		None => builder,
+	};
+
+	let builder = match working_directory {
+		Some(working_directory) => builder.with_working_directory(working_directory),
+		None => builder,
+	};
+
+	let builder = env.into_iter().fold(builder, |builder, (key, value)| {
+		builder.with_env(key, value)
+	});
+
+	let builder = removed_env
+		.into_iter()
+		.fold(builder, |builder, key| builder.without_env(key))
+		.with_wrapper(wrapper);
+
+	trace!("Constructing launcher");
+	builder.build().await.map_err(|err| {
+		error!(?err, "Failed to construct launcher from manifest");
+
+		err.to_string()
+	})
+}
+
+/// Writes a script reproducing the launch, a batch file for `.bat` and `.cmd`
+/// paths and a shell script otherwise
+#[tauri::command]
+pub async fn export_launch_script(
+	root: PathBuf,
+	id: String,
+	vars: HashMap<String, String>,
+	options: Option<LaunchOptions>,
+	path: PathBuf,
+) -> Result<(), String> {
+	let launcher = build_launcher(&root, &id, vars, options.unwrap_or_default()).await?;
+	let plan = launcher.prepare().await.map_err(|it| it.to_string())?;
+
+	// The script runs the game later on its own, with the shared natives of
+	// the version
+	launcher
+		.extract_natives(&launcher.natives_directory())
+		.await
+		.map_err(|it| it.to_string())?;
+
+	launcher
+		.prepare_files()
+		.await
+		.map_err(|it| it.to_string())?;
+
+	let is_batch = path
+		.extension()
+		.is_some_and(|it| it.eq_ignore_ascii_case("bat") || it.eq_ignore_ascii_case("cmd"));
+
+	let written = if is_batch {
+		plan.write_batch_script(&path).await
+	} else {
+		plan.write_shell_script(&path).await
+	};
+
+	written.map_err(|it| it.to_string())?;
+
+	info!("Launch script of {id} written to {path:?}");
+
+	Ok(())
+}
+
 #[tauri::command]
 pub async fn launch(
 	handle: AppHandle,
@@ -23,13 +184,26 @@ pub async fn launch(
 	root: PathBuf,
 	id: String,
 	vars: HashMap<String, String>,
+	options: Option<LaunchOptions>,
 ) -> Result<(), String> {
-	trace!("Constructing launcher");
-	let mut launcher = Launcher::try_from_root(&root, &id).await.map_err(|err| {
-		error!(?err, "Failed to construct launcher from manifest");
+	// Options are left out, their environment may hold credentials as well
+	let redacted: HashMap<_, _> = vars
+		.iter()
+		.map(|(key, value)| {
+			if SECRET_PLACEHOLDERS.contains(&key.as_str()) {
+				(key, "<redacted>")
+			} else {
+				(key, value.as_str())
+			}
+		})
+		.collect();
+	trace!(vars = ?redacted);
 
-		err.to_string()
-	})?;
+	let launcher = build_launcher(&root, &id, vars, options.unwrap_or_default()).await?;
+
+	for diagnostic in launcher.manifest.validate() {
+		warn!(?diagnostic, "Manifest {id}");
+	}
 
 	// trace!("Getting authlib injector");
 	// let authlib_injector_path = handle
@@ -51,20 +225,92 @@ pub async fn launch(
 	// 		"-Dauthlibinjector.usernameCheck=enabled".into()
 	// 	]);
 
-	trace!(?vars);
-	launcher.vars = vars;
+	let mut process = launcher.launch().await.map_err(|it| it.to_string())?;
+	let pid = process.pid();
+	info!("Version {id} launched, pid {pid:?}");
 
-	let (stdo, stde, mut rx) = launcher.launch().await.map_err(|it| it.to_string())?;
-	info!("Version {id} launched");
+	let (stop_tx, mut stop_rx) = mpsc::channel(1);
+	let mut stopped = false;
 
-	while let Some(msg) = rx.recv().await {
-		trace!("{id}: {}", msg.trim_end());
-		window.emit("log", msg).map_err(|it| it.to_string())?;
+	if let Some(pid) = pid {
+		stops().lock().await.insert(pid, stop_tx);
+
+		if let Err(err) = window.emit("launched", pid) {
+			warn!(?err, "Unable to emit game pid");
+		}
 	}
 
-	let (a, b) = tokio::join!(stdo, stde);
-	a.map_err(|it| it.to_string())?;
-	b.map_err(|it| it.to_string())?;
+	let mut rx = parse_output(
+		process
+			.take_output()
+			.expect("Output of a new process is not taken"),
+	);
 
-	Ok(())
+	loop {
+		tokio::select! {
+			entry = rx.recv() => {
+				let Some(entry) = entry else {
+					break;
+				};
+
+				trace!("{id}: {:?}", entry);
+
+				if let Err(err) = window.emit("log", entry) {
+					warn!(?err, "Unable to emit game output");
+				}
+			}
+			Some(force) = stop_rx.recv() => {
+				info!("Stopping version {id}, force: {force}");
+
+				let result = if force {
+					process.kill().await
+				} else {
+					process.terminate()
+				};
+
+				match result {
+					Ok(()) => stopped = true,
+					Err(err) => warn!(?err, "Unable to stop the game"),
+				}
+			}
+		}
+	}
+
+	if let Some(pid) = pid {
+		stops().lock().await.remove(&pid);
+	}
+
+	let exit = process.wait_for_exit().await.map_err(|it| it.to_string())?;
+	info!("Version {id} exited: {:?}", exit.kind);
+
+	// A stopped game exits with the signal, which is not a crash
+	if exit.kind.is_normal() || stopped {
+		return Ok(());
+	}
+
+	let message = exit
+		.crash
+		.as_ref()
+		.and_then(|it| it.description.clone())
+		.unwrap_or_else(|| format!("Game exited with {:?}", exit.kind));
+
+	if let Err(err) = window.emit("crash", &exit) {
+		warn!(?err, "Unable to emit crash report");
+	}
+
+	Err(message)
+}
+
+/// Asks a game started by [`launch`] to close, or kills it when `force` is set
+#[tauri::command]
+pub async fn stop_game(pid: u32, force: bool) -> Result<(), String> {
+	let stops = stops().lock().await;
+
+	let stop = stops
+		.get(&pid)
+		.ok_or_else(|| format!("Game {pid} is not running"))?;
+
+	stop
+		.try_send(force)
+		.map_err(|_| format!("Game {pid} is already being stopped"))
 }
@@ -0,0 +1,138 @@
+use {
+	rsmc::launcher::{
+		ServerConsole,
+		ServerLauncher,
+	},
+	std::{
+		collections::{
+			hash_map::Entry,
+			HashMap,
+		},
+		path::{
+			Path,
+			PathBuf,
+		},
+		sync::{
+			Arc,
+			OnceLock,
+		},
+	},
+	tauri::{
+		Manager,
+		Window,
+	},
+	tokio::sync::Mutex,
+	tracing::{
+		error,
+		info,
+		trace,
+	},
+};
+
+/// Console of a running server, locked on its own so a server that stops
+/// reading commands only blocks its own ones
+type SharedConsole = Arc<Mutex<ServerConsole>>;
+
+/// Consoles of running servers by their instance directories, reserved with
+/// `None` while the server starts
+static CONSOLES: OnceLock<Mutex<HashMap<PathBuf, Option<SharedConsole>>>> = OnceLock::new();
+
+fn consoles() -> &'static Mutex<HashMap<PathBuf, Option<SharedConsole>>> {
+	CONSOLES.get_or_init(Default::default)
+}
+
+#[tauri::command]
+pub async fn launch_server(
+	window: Window,
+	root: PathBuf,
+	id: String,
+	instance: PathBuf,
+	accept_eula: bool,
+	properties: HashMap<String, String>,
+) -> Result<(), String> {
+	match consoles().lock().await.entry(instance.clone()) {
+		Entry::Occupied(_) => return Err(format!("Server in {instance:?} is already running")),
+		Entry::Vacant(entry) => {
+			entry.insert(None);
+		}
+	}
+
+	let result = run_server(window, &root, &id, &instance, accept_eula, properties).await;
+
+	consoles().lock().await.remove(&instance);
+
+	result
+}
+
+async fn run_server(
+	window: Window,
+	root: &Path,
+	id: &str,
+	instance: &Path,
+	accept_eula: bool,
+	properties: HashMap<String, String>,
+) -> Result<(), String> {
+	trace!("Constructing server launcher");
+	let mut launcher = ServerLauncher::try_from_root(root, id, instance)
+		.await
+		.map_err(|err| {
+			error!(?err, "Failed to construct server launcher from manifest");
+
+			err.to_string()
+		})?;
+
+	launcher.accept_eula = accept_eula;
+	launcher.properties.extend(properties);
+
+	let mut process = launcher.launch().await.map_err(|it| it.to_string())?;
+	info!("Server {id} launched in {instance:?}");
+
+	if let Some(console) = consoles().lock().await.get_mut(instance) {
+		*console = Some(Arc::new(Mutex::new(process.console)));
+	}
+
+	let mut emitted = Ok(());
+
+	while let Some(line) = process.output.recv().await {
+		trace!("{id}: {}", line.trim_end());
+
+		emitted = window.emit("server-log", line);
+
+		if emitted.is_err() {
+			break;
+		}
+	}
+
+	// Nobody would see the server or be able to stop it anymore
+	if let Err(err) = emitted {
+		error!(?err, "Unable to emit server output, killing server {id}");
+
+		if let Err(err) = process.child.kill().await {
+			error!(?err, "Unable to kill server {id}");
+		}
+
+		return Err(err.to_string());
+	}
+
+	let status = process.child.wait().await.map_err(|it| it.to_string())?;
+	info!("Server {id} exited with {status}");
+
+	Ok(())
+}
+
+#[tauri::command]
+pub async fn server_command(instance: PathBuf, command: String) -> Result<(), String> {
+	let console = consoles()
+		.lock()
+		.await
+		.get(&instance)
+		.and_then(Option::clone)
+		.ok_or_else(|| format!("Server in {instance:?} is not running"))?;
+
+	console
+		.lock()
+		.await
+		.send_command(&command)
+		.await
+		.map_err(|it| it.to_string())
+}
@@ -1,7 +1,9 @@
+mod distros;
 mod launch;
 mod lookup;
 
 pub use {
+	distros::*,
 	launch::*,
 	lookup::*,
 };
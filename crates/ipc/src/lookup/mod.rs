@@ -1,4 +1,19 @@
-use std::path::Path;
+use {
+	rsmc::{
+		install::VersionIndexClient,
+		spec::VersionIndex,
+	},
+	std::{
+		path::Path,
+		sync::OnceLock,
+	},
+};
+
+static VERSION_INDEX: OnceLock<VersionIndexClient> = OnceLock::new();
+
+pub(crate) fn version_index() -> &'static VersionIndexClient {
+	VERSION_INDEX.get_or_init(VersionIndexClient::default)
+}
 
 #[tauri::command]
 pub async fn lookup_versions(path: &Path) -> Result<Vec<lookup::VersionOverview>, String> {
@@ -6,3 +21,10 @@ pub async fn lookup_versions(path: &Path) -> Result<Vec<lookup::VersionOverview>
 		.await
 		.map_err(|err| err.to_string())
 }
+
+#[tauri::command]
+pub async fn lookup_remote_versions() -> Result<VersionIndex, String> {
+	let index = version_index().get().await.map_err(|err| err.to_string())?;
+
+	Ok(index.as_ref().clone())
+}
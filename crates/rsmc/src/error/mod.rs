@@ -15,6 +15,14 @@ pub enum Error {
 	#[error(transparent)]
 	Network(#[from] reqwest::Error),
 
+	#[cfg(feature = "download")]
+	#[error(transparent)]
+	Download(Box<download::DownloadError>),
+
+	#[cfg(feature = "zip")]
+	#[error(transparent)]
+	Zip(#[from] zip::result::ZipError),
+
 	#[cfg(feature = "url")]
 	#[error(transparent)]
 	UrlParse(#[from] url::ParseError),
@@ -25,9 +33,42 @@ pub enum Error {
 	#[error("Inherited manifest contains not enough data to launch or install from it. Consider merging it with parent")]
 	Inherited,
 
+	#[error("Manifest inheritance cycle: {0}")]
+	InheritanceCycle(String),
+
 	#[error("You're using unsupported platform")]
 	UnsupportedPlatform,
 
+	#[error("Invalid artifact name: {0}")]
+	InvalidArtifactName(String),
+
+	#[error("Unable to resolve library: {0}")]
+	UnresolvedLibrary(String),
+
+	#[error("Not a valid java executable: {0:?}")]
+	InvalidJava(std::path::PathBuf),
+
+	#[error("No java {0} installation found")]
+	JavaNotFound(u32),
+
+	#[error("Invalid launcher configuration: {0}")]
+	InvalidLauncherConfig(String),
+
+	#[error("Unresolved placeholders: {}", .0.join(", "))]
+	UnresolvedPlaceholders(Vec<String>),
+
+	#[error("Version {0} has no dedicated server")]
+	NoServer(String),
+
+	#[error("Minecraft EULA has to be accepted to run the server")]
+	EulaNotAccepted,
+
+	#[error("Path leaves its directory: {0}")]
+	UnsafePath(String),
+
+	#[error("Checksum mismatch: {0}")]
+	ChecksumMismatch(String),
+
 	#[error("Invalid manifest: {0}")]
 	InvalidManifest(String),
 
@@ -37,3 +78,10 @@ pub enum Error {
 	#[error("Not yet supported feature: {0}")]
 	NotYetSupported(String),
 }
+
+#[cfg(feature = "download")]
+impl From<download::DownloadError> for Error {
+	fn from(value: download::DownloadError) -> Self {
+		Self::Download(Box::new(value))
+	}
+}
@@ -0,0 +1,209 @@
+use {
+	crate::{
+		spec::*,
+		tracing::{
+			debug,
+			trace,
+			warn,
+		},
+		Error,
+	},
+	download::{
+		download_all,
+		DownloadEvent,
+		Item,
+	},
+	reqwest::Client,
+	std::{
+		path::{
+			Path,
+			PathBuf,
+		},
+		sync::Arc,
+	},
+	tokio::{
+		fs,
+		sync::mpsc::Sender,
+	},
+	tokio_util::sync::CancellationToken,
+	url::Url,
+};
+
+pub const JAVA_RUNTIME_INDEX: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+/// Installs Mojang java runtimes into `root/jre/<component>`
+#[derive(Debug, Clone)]
+pub struct JavaRuntimeInstaller {
+	pub client: Client,
+	pub index_url: Url,
+}
+
+/// Downloads of a runtime component and the manifest to finish the
+/// installation with once they are done
+#[derive(Debug, Clone)]
+pub struct JavaRuntimeInstall {
+	pub dir: PathBuf,
+	pub manifest: JavaRuntimeManifest,
+	pub items: Vec<Item>,
+}
+
+impl Default for JavaRuntimeInstaller {
+	fn default() -> Self {
+		Self::new(Client::new())
+	}
+}
+
+impl JavaRuntimeInstaller {
+	pub fn new(client: Client) -> Self {
+		Self {
+			client,
+			index_url: Url::parse(JAVA_RUNTIME_INDEX).expect("Invalid default java runtime index url"),
+		}
+	}
+
+	pub fn with_index_url(mut self, index_url: Url) -> Self {
+		self.index_url = index_url;
+		self
+	}
+
+	async fn fetch<T: serde::de::DeserializeOwned>(&self, url: Url) -> Result<T, Error> {
+		debug!("Fetching {}", url);
+
+		let content = self
+			.client
+			.get(url)
+			.send()
+			.await?
+			.error_for_status()?
+			.text()
+			.await?;
+
+		Ok(serde_json::from_str(&content)?)
+	}
+
+	pub async fn fetch_index(&self) -> Result<JavaRuntimeIndex, Error> {
+		self.fetch(self.index_url.clone()).await
+	}
+
+	pub async fn fetch_manifest(&self, runtime: &JavaRuntime) -> Result<JavaRuntimeManifest, Error> {
+		self.fetch(runtime.manifest.url.clone()).await
+	}
+
+	/// Plans the installation of the component for the platform described by
+	/// `ctx`
+	pub async fn prepare(
+		&self,
+		root: &Path,
+		component: &str,
+		ctx: &RuleContext,
+	) -> Result<JavaRuntimeInstall, Error> {
+		let platform = JavaRuntimeIndex::platform(&ctx.os, &ctx.arch)?;
+		let index = self.fetch_index().await?;
+
+		let runtime = index
+			.find(platform, component)
+			.ok_or_else(|| Error::NotYetSupported(format!("Java runtime {component} for {platform}")))?;
+
+		debug!(
+			?component,
+			version = runtime.version.name,
+			"Java runtime found"
+		);
+
+		let manifest = self.fetch_manifest(runtime).await?;
+		let dir = root.join("jre").join(component);
+
+		let items = manifest
+			.files
+			.iter()
+			.filter_map(|(name, file)| match file {
+				JavaRuntimeFile::File { downloads, .. } => Some((name, downloads)),
+				_ => None,
+			})
+			.map(|(name, downloads)| {
+				Ok(Item {
+					url: downloads.raw.url.clone(),
+					path: dir.join(safe_relative_path(name)?),
+					known_size: Some(downloads.raw.size),
+					known_sha: Some(downloads.raw.sha1.clone()),
+					ignore_integrity: false,
+				})
+			})
+			.collect::<Result<_, Error>>()?;
+
+		Ok(JavaRuntimeInstall {
+			dir,
+			manifest,
+			items,
+		})
+	}
+
+	/// Downloads and finalizes the component in one go
+	pub async fn install(
+		&self,
+		root: &Path,
+		component: &str,
+		ctx: &RuleContext,
+		sender: Arc<Sender<DownloadEvent>>,
+		token: Arc<CancellationToken>,
+	) -> Result<PathBuf, Error> {
+		let install = self.prepare(root, component, ctx).await?;
+
+		download_all(&self.client, install.items.clone(), sender, token, 16).await?;
+		install.finalize().await?;
+
+		Ok(install.dir)
+	}
+}
+
+impl JavaRuntimeInstall {
+	/// Creates directories and links and marks executables once all files are
+	/// downloaded
+	pub async fn finalize(&self) -> Result<(), Error> {
+		for (name, file) in &self.manifest.files {
+			let path = self.dir.join(safe_relative_path(name)?);
+
+			match file {
+				JavaRuntimeFile::Directory => fs::create_dir_all(&path).await?,
+				JavaRuntimeFile::Link { target } => {
+					if let Some(parent) = path.parent() {
+						fs::create_dir_all(parent).await?;
+					}
+
+					if fs::symlink_metadata(&path).await.is_ok() {
+						fs::remove_file(&path).await?;
+					}
+
+					trace!("{} -> {}", name, target);
+
+					#[cfg(target_family = "unix")]
+					let linked = fs::symlink(target, &path).await;
+					#[cfg(target_family = "windows")]
+					let linked = fs::symlink_file(target, &path).await;
+
+					if let Err(err) = linked {
+						warn!(?err, "Unable to create link {}", name);
+					}
+				}
+				JavaRuntimeFile::File { executable, .. } => {
+					#[cfg(target_family = "unix")]
+					if *executable {
+						use std::{
+							fs::Permissions,
+							os::unix::fs::PermissionsExt,
+						};
+
+						fs::set_permissions(&path, Permissions::from_mode(0o755)).await?;
+					}
+
+					#[cfg(not(target_family = "unix"))]
+					let _ = executable;
+				}
+			}
+		}
+
+		debug!(dir = ?self.dir, "Java runtime installed");
+
+		Ok(())
+	}
+}
@@ -0,0 +1,255 @@
+use {
+	crate::{
+		spec::*,
+		tracing::{
+			debug,
+			trace,
+			warn,
+		},
+		Error,
+	},
+	reqwest::{
+		Client,
+		StatusCode,
+	},
+	std::path::{
+		Component,
+		Path,
+		PathBuf,
+	},
+	url::Url,
+};
+
+pub const MOJANG_LIBRARIES: &str = "https://libraries.minecraft.net/";
+pub const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2/";
+
+/// Concrete download of a single library file
+#[derive(Debug, Clone)]
+pub struct LibraryDownload {
+	pub url: Url,
+	/// Target path relative to the libraries directory
+	pub path: PathBuf,
+	pub sha1: Option<String>,
+	pub size: Option<u64>,
+}
+
+/// Resolves libraries into downloads, looking up artifacts without a known
+/// location in the configured maven repositories
+#[derive(Debug, Clone)]
+pub struct MavenResolver {
+	pub client: Client,
+	pub repositories: Vec<Url>,
+}
+
+impl Default for MavenResolver {
+	fn default() -> Self {
+		Self::new(Client::new())
+	}
+}
+
+fn url_path(path: &Path) -> String {
+	path
+		.components()
+		.filter_map(|it| match it {
+			Component::Normal(it) => Some(it.to_string_lossy()),
+			_ => None,
+		})
+		.collect::<Vec<_>>()
+		.join("/")
+}
+
+/// Url with a trailing slash, so joining keeps its last path segment
+pub(crate) fn as_base(url: &Url) -> Url {
+	let mut url = url.clone();
+
+	if !url.path().ends_with('/') {
+		url.set_path(&format!("{}/", url.path()));
+	}
+
+	url
+}
+
+impl MavenResolver {
+	pub fn new(client: Client) -> Self {
+		Self {
+			client,
+			repositories: [MOJANG_LIBRARIES, MAVEN_CENTRAL]
+				.into_iter()
+				.map(|it| Url::parse(it).expect("Invalid default repository url"))
+				.collect(),
+		}
+	}
+
+	pub fn with_repositories(mut self, repositories: Vec<Url>) -> Self {
+		self.repositories = repositories;
+		self
+	}
+
+	/// Downloads required by the library on the platform described by `ctx`.
+	/// Empty if the library is not allowed by its rules.
+	pub async fn resolve(
+		&self,
+		library: &Library,
+		ctx: &RuleContext,
+	) -> Result<Vec<LibraryDownload>, Error> {
+		use Library::*;
+
+		match library {
+			Common(it) => Ok(
+				self
+					.resolve_artifact(&it.name, &it.downloads.artifact)
+					.await?
+					.into_iter()
+					.collect(),
+			),
+			Seminative(it) => {
+				if !Rule::unpack_all(&it.rules, ctx) {
+					return Ok(Vec::new());
+				}
+
+				Ok(
+					self
+						.resolve_artifact(&it.name, &it.downloads.artifact)
+						.await?
+						.into_iter()
+						.collect(),
+				)
+			}
+			Native(it) => {
+				if !Rule::unpack_all(&it.rules, ctx) {
+					return Ok(Vec::new());
+				}
+
+				let classifier = it.natives.get_classifier_name(ctx)?;
+				let native = it
+					.downloads
+					.classifiers
+					.get(&classifier)
+					.ok_or(Error::InvalidManifest("Missing native classifier".into()))?;
+
+				let mut downloads = Vec::new();
+
+				if let Some(artifact) = &it.downloads.artifact {
+					downloads.extend(self.resolve_artifact(&it.name, artifact).await?);
+				}
+
+				downloads.extend(self.resolve_artifact(&it.name, native).await?);
+
+				Ok(downloads)
+			}
+			Custom(it) => Ok(vec![self.resolve_custom(it).await?]),
+		}
+	}
+
+	/// Download of the artifact, none for artifacts with an empty url, which
+	/// are generated locally, e.g. by the Forge installer
+	async fn resolve_artifact(
+		&self,
+		name: &ArtifactName,
+		artifact: &Artifact,
+	) -> Result<Option<LibraryDownload>, Error> {
+		let url = match artifact.url.as_deref() {
+			Some("") => {
+				trace!("{} is provided locally", name);
+
+				return Ok(None);
+			}
+			Some(url) => Url::parse(url)?,
+			None => {
+				trace!("No url for {}, looking up in repositories", name);
+
+				let (url, _) = self
+					.lookup(None, &url_path(&artifact.path))
+					.await?
+					.ok_or_else(|| Error::UnresolvedLibrary(name.to_string()))?;
+
+				url
+			}
+		};
+
+		Ok(Some(LibraryDownload {
+			url,
+			path: artifact.path.clone(),
+			sha1: Some(artifact.sha1.clone()),
+			size: Some(artifact.size),
+		}))
+	}
+
+	async fn resolve_custom(&self, library: &CustomLibrary) -> Result<LibraryDownload, Error> {
+		let path = library.name.to_file_path();
+		let url_path = url_path(&path);
+
+		let (url, sha1) = match (&library.url, &library.sha1) {
+			(Some(base), Some(sha1)) => (as_base(base).join(&url_path)?, Some(sha1.clone())),
+			(base, _) => match self.lookup(base.as_ref(), &url_path).await? {
+				Some((url, sha1)) => (url, Some(sha1)),
+				None => match base {
+					Some(base) => {
+						warn!("No checksum available for {}", library.name);
+
+						(as_base(base).join(&url_path)?, None)
+					}
+					None => return Err(Error::UnresolvedLibrary(library.name.to_string())),
+				},
+			},
+		};
+
+		Ok(LibraryDownload {
+			url,
+			path,
+			sha1,
+			size: library.size,
+		})
+	}
+
+	/// Finds the first repository publishing a valid `.sha1` sidecar for the
+	/// file. Fails if the file is only found with malformed ones, since it
+	/// could not be verified.
+	async fn lookup(
+		&self,
+		preferred: Option<&Url>,
+		path: &str,
+	) -> Result<Option<(Url, String)>, Error> {
+		let mut malformed_in = None;
+
+		for base in preferred.into_iter().chain(&self.repositories) {
+			let url = as_base(base).join(path)?;
+			let sidecar = Url::parse(&format!("{url}.sha1"))?;
+
+			trace!("Checking {}", sidecar);
+
+			let response = match self.client.get(sidecar).send().await {
+				Ok(it) => it,
+				Err(err) => {
+					warn!(?err, "Repository {} is unreachable", base);
+					continue;
+				}
+			};
+
+			match response.status() {
+				StatusCode::OK => {
+					let body = response.text().await?;
+					// Some repositories answer with an error page instead of a 404
+					let Some(sha1) = body.split_whitespace().next().filter(|it| is_sha1(it)) else {
+						warn!("Ignoring malformed checksum of {} in {}", path, base);
+
+						malformed_in.get_or_insert(base);
+						continue;
+					};
+
+					debug!("Found {} in {}", path, base);
+
+					return Ok(Some((url, sha1.to_ascii_lowercase())));
+				}
+				status => trace!(?status, "{} is missing in {}", path, base),
+			}
+		}
+
+		match malformed_in {
+			Some(base) => Err(Error::UnresolvedLibrary(format!(
+				"{path}, malformed checksum in {base}"
+			))),
+			None => Ok(None),
+		}
+	}
+}
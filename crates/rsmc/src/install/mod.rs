@@ -0,0 +1,11 @@
+mod java;
+mod maven;
+mod plan;
+mod version_index;
+
+pub use {
+	java::*,
+	maven::*,
+	plan::*,
+	version_index::*,
+};
@@ -0,0 +1,221 @@
+use {
+	super::MavenResolver,
+	crate::{
+		spec::*,
+		tracing::{
+			debug,
+			trace,
+		},
+		Error,
+	},
+	download::Item,
+	reqwest::Client,
+	std::{
+		collections::HashSet,
+		path::{
+			Path,
+			PathBuf,
+		},
+	},
+	tokio::fs::{
+		self,
+		File,
+	},
+	url::Url,
+};
+
+pub const RESOURCES: &str = "https://resources.download.minecraft.net/";
+
+fn item(url: Url, path: PathBuf, sha1: &str, size: u64) -> Item {
+	Item {
+		url,
+		path,
+		known_size: Some(size),
+		known_sha: Some(sha1.to_owned()),
+		ignore_integrity: false,
+	}
+}
+
+/// Turns a manifest into a list of downloads required to launch it
+#[derive(Debug, Clone)]
+pub struct InstallPlanner {
+	pub client: Client,
+	pub resolver: MavenResolver,
+	pub resources_url: Url,
+}
+
+impl Default for InstallPlanner {
+	fn default() -> Self {
+		Self::new(Client::new())
+	}
+}
+
+impl InstallPlanner {
+	pub fn new(client: Client) -> Self {
+		Self {
+			resolver: MavenResolver::new(client.clone()),
+			client,
+			resources_url: Url::parse(RESOURCES).expect("Invalid default resources url"),
+		}
+	}
+
+	pub fn with_resolver(mut self, resolver: MavenResolver) -> Self {
+		self.resolver = resolver;
+		self
+	}
+
+	pub fn with_resources_url(mut self, resources_url: Url) -> Self {
+		self.resources_url = resources_url;
+		self
+	}
+
+	/// Reads the asset index from the root, fetching it first if it is
+	/// missing or corrupted. A fetched index is only stored once it matches
+	/// the checksum of the resource.
+	pub async fn asset_index(
+		&self,
+		root: &Path,
+		resource: &AssetIndexResource,
+	) -> Result<AssetIndex, Error> {
+		let path = asset_index_path(root, &resource.id);
+
+		let is_valid = match File::open(&path).await {
+			Ok(mut file) => integrity::check(&mut file, &resource.sha1)
+				.await
+				.unwrap_or(false),
+			Err(_) => false,
+		};
+
+		let content = if is_valid {
+			fs::read(&path).await?
+		} else {
+			debug!("Fetching asset index {}", resource.id);
+
+			let content = self
+				.client
+				.get(resource.url.clone())
+				.send()
+				.await?
+				.error_for_status()?
+				.bytes()
+				.await?;
+
+			if !integrity::check_bytes(&content, &resource.sha1) {
+				return Err(Error::ChecksumMismatch(format!(
+					"asset index {}",
+					resource.id
+				)));
+			}
+
+			if let Some(parent) = path.parent() {
+				fs::create_dir_all(parent).await?;
+			}
+
+			fs::write(&path, &content).await?;
+
+			content.to_vec()
+		};
+
+		Ok(serde_json::from_slice(&content)?)
+	}
+
+	pub async fn plan(
+		&self,
+		root: &Path,
+		manifest: &NativeManifest,
+		ctx: &RuleContext,
+	) -> Result<Vec<Item>, Error> {
+		let mut items = Vec::new();
+
+		let libraries_dir = root.join("libraries");
+		let assets_dir = root.join("assets");
+		let jar_id = manifest.jar_id();
+
+		let client = &manifest.downloads.client;
+		items.push(item(
+			client.url.clone(),
+			root
+				.join("versions")
+				.join(jar_id)
+				.join(format!("{jar_id}.jar")),
+			&client.sha1,
+			client.size,
+		));
+
+		for lib in &manifest.libraries {
+			for download in self.resolver.resolve(lib, ctx).await? {
+				trace!("Library: {}", download.url);
+
+				items.push(Item {
+					url: download.url,
+					path: libraries_dir.join(download.path),
+					known_size: download.size,
+					known_sha: download.sha1,
+					ignore_integrity: false,
+				});
+			}
+		}
+
+		let resource = &manifest.asset_index;
+		items.push(item(
+			resource.url.clone(),
+			asset_index_path(root, &resource.id),
+			&resource.sha1,
+			resource.size,
+		));
+
+		let index = self.asset_index(root, resource).await?;
+		let objects_dir = assets_dir.join("objects");
+
+		// Many names share an object, each file is downloaded once
+		let mut hashes = HashSet::new();
+
+		for object in index.objects.values() {
+			if !hashes.insert(&object.hash) {
+				continue;
+			}
+
+			let object_path = object.object_path();
+
+			items.push(item(
+				self.resources_url.join(&object_path)?,
+				objects_dir.join(object_path),
+				&object.hash,
+				object.size,
+			));
+		}
+
+		if let Some(Logging { client, .. }) = &manifest.logging {
+			let file = &client.file;
+
+			items.push(item(
+				file.url.clone(),
+				log_config_path(root, file),
+				&file.sha1,
+				file.size,
+			));
+		}
+
+		debug!(id = manifest.id, count = items.len(), "Install planned");
+
+		Ok(items)
+	}
+
+	/// Downloads required to run the dedicated server of the version, the
+	/// server jar bundles its libraries so it is the only one. It is kept with
+	/// the version the downloads come from, e.g. the parent of a modded one.
+	pub fn plan_server(&self, root: &Path, manifest: &NativeManifest) -> Result<Vec<Item>, Error> {
+		let server = manifest
+			.downloads
+			.server
+			.as_ref()
+			.ok_or_else(|| Error::NoServer(manifest.id.clone()))?;
+
+		Ok(vec![item(
+			server.url.clone(),
+			server_jar_path(root, manifest.jar_id()),
+			&server.sha1,
+			server.size,
+		)])
+	}
+}
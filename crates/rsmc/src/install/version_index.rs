@@ -0,0 +1,355 @@
+use {
+	super::as_base,
+	crate::{
+		spec::*,
+		tracing::{
+			debug,
+			warn,
+		},
+		Error,
+	},
+	reqwest::Client,
+	std::{
+		path::{
+			Path,
+			PathBuf,
+		},
+		sync::Arc,
+	},
+	tokio::{
+		fs,
+		sync::RwLock,
+	},
+	url::Url,
+};
+
+pub const PISTON_META: &str = "https://piston-meta.mojang.com/";
+
+const VERSION_INDEX_PATH: &str = "mc/game/version_manifest_v2.json";
+const MOJANG_META_HOSTS: [&str; 2] = ["piston-meta.mojang.com", "launchermeta.mojang.com"];
+
+/// Fetches the Mojang version index and version manifests, optionally through
+/// a mirror. The index is kept in memory after the first successful fetch and
+/// persisted to `cache_path` to be used when the network is unavailable.
+#[derive(Debug, Clone)]
+pub struct VersionIndexClient {
+	pub client: Client,
+	pub base_url: Url,
+	pub cache_path: Option<PathBuf>,
+	cached: Arc<RwLock<Option<Arc<VersionIndex>>>>,
+}
+
+impl Default for VersionIndexClient {
+	fn default() -> Self {
+		Self::new(Client::new())
+	}
+}
+
+impl VersionIndexClient {
+	pub fn new(client: Client) -> Self {
+		Self {
+			client,
+			base_url: Url::parse(PISTON_META).expect("Invalid default meta url"),
+			cache_path: None,
+			cached: Arc::new(RwLock::new(None)),
+		}
+	}
+
+	pub fn with_base_url(mut self, base_url: Url) -> Self {
+		self.base_url = as_base(&base_url);
+		self
+	}
+
+	pub fn with_cache_path(mut self, cache_path: impl Into<PathBuf>) -> Self {
+		self.cache_path = Some(cache_path.into());
+		self
+	}
+
+	/// Rewrites urls pointing to Mojang meta servers to the configured base url
+	pub fn mirror(&self, url: &Url) -> Result<Url, Error> {
+		match url.host_str() {
+			Some(host) if MOJANG_META_HOSTS.contains(&host) => {
+				Ok(as_base(&self.base_url).join(url.path().trim_start_matches('/'))?)
+			}
+			_ => Ok(url.clone()),
+		}
+	}
+
+	async fn fetch_text(&self, url: Url) -> Result<String, Error> {
+		debug!("Fetching {}", url);
+
+		Ok(
+			self
+				.client
+				.get(url)
+				.send()
+				.await?
+				.error_for_status()?
+				.text()
+				.await?,
+		)
+	}
+
+	/// Version index from memory, fetching it on first use
+	pub async fn get(&self) -> Result<Arc<VersionIndex>, Error> {
+		if let Some(index) = self.cached.read().await.as_ref() {
+			return Ok(index.clone());
+		}
+
+		self.refresh().await
+	}
+
+	async fn write_cache(cache_path: &Path, content: &str) -> Result<(), Error> {
+		if let Some(parent) = cache_path.parent() {
+			fs::create_dir_all(parent).await?;
+		}
+
+		fs::write(cache_path, content).await?;
+
+		Ok(())
+	}
+
+	/// Fetches the manifest of the entry and checks it against the index
+	async fn fetch_verified(&self, entry: &VersionIndexEntry) -> Result<String, Error> {
+		let content = self.fetch_text(self.mirror(&entry.url)?).await?;
+
+		if !integrity::check_bytes(content.as_bytes(), &entry.sha1) {
+			return Err(Error::ChecksumMismatch(format!("manifest of {}", entry.id)));
+		}
+
+		Ok(content)
+	}
+
+	/// Fetches the version index with its raw content
+	async fn fetch_index(&self) -> Result<(VersionIndex, String), Error> {
+		let content = self
+			.fetch_text(as_base(&self.base_url).join(VERSION_INDEX_PATH)?)
+			.await?;
+
+		Ok((serde_json::from_str(&content)?, content))
+	}
+
+	async fn read_cache(&self) -> Option<VersionIndex> {
+		let content = fs::read_to_string(self.cache_path.as_ref()?).await.ok()?;
+
+		serde_json::from_str(&content)
+			.map_err(|err| warn!(?err, "Ignoring corrupted version index cache"))
+			.ok()
+	}
+
+	/// Fetches the version index bypassing the in-memory copy. Only an index
+	/// that parses replaces the cached one, which is used when the fetch fails.
+	pub async fn refresh(&self) -> Result<Arc<VersionIndex>, Error> {
+		let index = match self.fetch_index().await {
+			Ok((index, content)) => {
+				if let Some(cache_path) = &self.cache_path {
+					if let Err(err) = Self::write_cache(cache_path, &content).await {
+						warn!(?err, "Failed to cache version index");
+					}
+				}
+
+				index
+			}
+			Err(err) => match self.read_cache().await {
+				Some(index) => {
+					warn!(?err, "Failed to fetch version index, using cached one");
+
+					index
+				}
+				None => return Err(err),
+			},
+		};
+
+		let index = Arc::new(index);
+
+		*self.cached.write().await = Some(index.clone());
+
+		Ok(index)
+	}
+
+	pub async fn fetch_manifest(&self, entry: &VersionIndexEntry) -> Result<Manifest, Error> {
+		let content = self.fetch_verified(entry).await?;
+
+		Ok(serde_json::from_str(&content)?)
+	}
+
+	/// Fetches the version manifest and stores it as
+	/// `versions/<id>/<id>.json` in the root
+	pub async fn install_manifest(
+		&self,
+		root: &Path,
+		entry: &VersionIndexEntry,
+	) -> Result<Manifest, Error> {
+		let content = self.fetch_verified(entry).await?;
+		let manifest = serde_json::from_str(&content)?;
+
+		let version_dir = root.join("versions").join(&entry.id);
+		fs::create_dir_all(&version_dir).await?;
+		fs::write(version_dir.join(format!("{}.json", entry.id)), content).await?;
+
+		Ok(manifest)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::*,
+		tokio::{
+			io::{
+				AsyncReadExt,
+				AsyncWriteExt,
+			},
+			net::TcpListener,
+		},
+	};
+
+	const MANIFEST: &str =
+		r#"{"id":"fabric","inheritsFrom":"1.20","mainClass":"a.Main","type":"release"}"#;
+
+	/// Serves the bodies by request path over plain HTTP, other paths get a
+	/// 404. Returns the base url of the server without a trailing slash.
+	async fn serve(routes: Vec<(String, String)>) -> Url {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+
+		tokio::spawn(async move {
+			while let Ok((mut stream, _)) = listener.accept().await {
+				let mut buf = vec![0; 4096];
+				let read = stream.read(&mut buf).await.unwrap_or(0);
+				let request = String::from_utf8_lossy(&buf[..read]);
+				let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+				let response = match routes.iter().find(|(it, _)| it == path) {
+					Some((_, body)) => format!(
+						"HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+						body.len()
+					),
+					None => {
+						"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_owned()
+					}
+				};
+
+				let _ = stream.write_all(response.as_bytes()).await;
+			}
+		});
+
+		Url::parse(&format!("http://{addr}/mirror")).unwrap()
+	}
+
+	fn index(sha1: &str) -> String {
+		format!(
+			r#"{{
+				"latest": {{ "release": "fabric", "snapshot": "fabric" }},
+				"versions": [{{
+					"id": "fabric",
+					"type": "release",
+					"url": "https://piston-meta.mojang.com/v1/packages/{sha1}/fabric.json",
+					"time": "2023-06-07T09:35:21+00:00",
+					"releaseTime": "2023-06-07T09:35:21+00:00",
+					"sha1": "{sha1}",
+					"complianceLevel": 1
+				}}]
+			}}"#
+		)
+	}
+
+	async fn client(manifest_sha1: &str, manifest: &str) -> VersionIndexClient {
+		let base_url = serve(vec![
+			(
+				format!("/mirror/{VERSION_INDEX_PATH}"),
+				index(manifest_sha1),
+			),
+			(
+				format!("/mirror/v1/packages/{manifest_sha1}/fabric.json"),
+				manifest.to_owned(),
+			),
+		])
+		.await;
+
+		VersionIndexClient::default().with_base_url(base_url)
+	}
+
+	fn temp_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("rsmc-test-{name}-{}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	#[tokio::test]
+	async fn fetches_through_mirror_without_trailing_slash() {
+		let sha1 = integrity::sha1_hex(MANIFEST.as_bytes());
+		let client = client(&sha1, MANIFEST).await;
+
+		let index = client.refresh().await.unwrap();
+		let entry = index.latest_release().unwrap();
+
+		assert!(matches!(
+			client.fetch_manifest(entry).await.unwrap(),
+			Manifest::Inherited(_)
+		));
+	}
+
+	#[tokio::test]
+	async fn rejects_manifest_with_wrong_checksum() {
+		let sha1 = integrity::sha1_hex(MANIFEST.as_bytes());
+		let client = client(&sha1, &MANIFEST.replace("a.Main", "b.Main")).await;
+		let root = temp_dir("checksum");
+
+		let index = client.refresh().await.unwrap();
+		let entry = index.latest_release().unwrap();
+		let result = client.install_manifest(&root, entry).await;
+
+		assert!(matches!(result, Err(Error::ChecksumMismatch(_))));
+		assert!(!root.join("versions").exists());
+	}
+
+	#[tokio::test]
+	async fn keeps_fetched_index_when_cache_is_not_writable() {
+		let sha1 = integrity::sha1_hex(MANIFEST.as_bytes());
+		let dir = temp_dir("cache-file");
+		let blocker = dir.join("file");
+		std::fs::write(&blocker, "").unwrap();
+
+		let client = client(&sha1, MANIFEST)
+			.await
+			.with_cache_path(blocker.join("index.json"));
+
+		assert_eq!(client.refresh().await.unwrap().versions.len(), 1);
+	}
+
+	#[tokio::test]
+	async fn falls_back_to_cached_index() {
+		let dir = temp_dir("cache");
+		let cache_path = dir.join("index.json");
+		std::fs::write(&cache_path, index(&"0".repeat(40))).unwrap();
+
+		let client = VersionIndexClient::default()
+			.with_base_url(serve(Vec::new()).await)
+			.with_cache_path(&cache_path);
+
+		assert_eq!(client.get().await.unwrap().latest.release, "fabric");
+	}
+
+	#[tokio::test]
+	async fn keeps_cached_index_over_invalid_one() {
+		let dir = temp_dir("cache-invalid");
+		let cache_path = dir.join("index.json");
+		let cached = index(&"0".repeat(40));
+		std::fs::write(&cache_path, &cached).unwrap();
+
+		let base_url = serve(vec![(
+			format!("/mirror/{VERSION_INDEX_PATH}"),
+			"<html>Bad Gateway</html>".to_owned(),
+		)])
+		.await;
+
+		let client = VersionIndexClient::default()
+			.with_base_url(base_url)
+			.with_cache_path(&cache_path);
+
+		assert_eq!(client.refresh().await.unwrap().latest.release, "fabric");
+		assert_eq!(std::fs::read_to_string(&cache_path).unwrap(), cached);
+	}
+}
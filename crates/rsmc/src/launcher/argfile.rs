@@ -0,0 +1,187 @@
+use {
+	super::{
+		JavaDiscovery,
+		LaunchPlan,
+		Launcher,
+		PROBE_EXECUTABLE_NAME,
+	},
+	crate::{
+		tracing::{
+			debug,
+			warn,
+		},
+		Error,
+	},
+	std::{
+		env,
+		io::ErrorKind,
+		path::{
+			Path,
+			PathBuf,
+		},
+		time::{
+			SystemTime,
+			UNIX_EPOCH,
+		},
+	},
+	tokio::{
+		fs::{
+			self,
+			OpenOptions,
+		},
+		io::AsyncWriteExt,
+		process::Command,
+	},
+};
+
+/// First java version reading arguments from `@argfiles`
+pub const ARGFILE_MIN_JAVA: u32 = 9;
+
+/// First java version reading `@argfiles` as UTF-8 instead of the platform
+/// charset
+pub const ARGFILE_UTF8_JAVA: u32 = 18;
+
+const ARGFILE_NAME: &str = "jvm.args";
+
+/// Quotes an argument for an `@argfile`, where backslashes only escape inside
+/// quotes
+pub fn argfile_quote(arg: &str) -> String {
+	let mut quoted = String::with_capacity(arg.len() + 2);
+
+	quoted.push('"');
+
+	for char in arg.chars() {
+		match char {
+			'\\' => quoted.push_str("\\\\"),
+			'"' => quoted.push_str("\\\""),
+			'\n' => quoted.push_str("\\n"),
+			'\r' => quoted.push_str("\\r"),
+			'\t' => quoted.push_str("\\t"),
+			'\x0c' => quoted.push_str("\\f"),
+			_ => quoted.push(char),
+		}
+	}
+
+	quoted.push('"');
+	quoted
+}
+
+impl LaunchPlan {
+	/// JVM arguments, classpath included, one per line. Written as UTF-8, see
+	/// [`ARGFILE_UTF8_JAVA`].
+	pub fn to_argfile(&self) -> String {
+		self
+			.jvm_args
+			.iter()
+			.map(|it| argfile_quote(it) + "\n")
+			.collect()
+	}
+
+	/// Same as [`LaunchPlan::command`] with the JVM arguments read from the
+	/// file
+	pub fn command_with_argfile(&self, argfile: &Path) -> Command {
+		let mut command = self.base_command();
+
+		command.arg(format!("@{}", argfile.display()));
+		command.arg(&self.main_class);
+		command.args(&self.game_args);
+
+		command
+	}
+
+	/// Writes the argfile into a new temporary directory, which is returned
+	pub(crate) async fn write_argfile(&self) -> Result<PathBuf, Error> {
+		let nanos = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|it| it.as_nanos())
+			.unwrap_or_default();
+
+		let dir = env::temp_dir().join(format!("rsmc-launch-{}-{nanos}", std::process::id()));
+
+		fs::create_dir(&dir).await?;
+
+		let path = dir.join(ARGFILE_NAME);
+
+		let mut options = OpenOptions::new();
+		options.write(true).create_new(true);
+
+		// Arguments may hold credentials, the file is never readable by others
+		#[cfg(target_family = "unix")]
+		options.mode(0o600);
+
+		let mut file = options.open(&path).await?;
+		file.write_all(self.to_argfile().as_bytes()).await?;
+		file.flush().await?;
+
+		debug!(?path, "JVM arguments written");
+
+		Ok(dir)
+	}
+}
+
+/// Path of the argfile in a directory from [`LaunchPlan::write_argfile`]
+pub(crate) fn argfile_path(dir: &Path) -> PathBuf {
+	dir.join(ARGFILE_NAME)
+}
+
+/// Removes the argfile directory once the game is closed
+pub(crate) async fn remove_argfile(dir: &Path) -> Result<(), Error> {
+	match fs::remove_dir_all(dir).await {
+		Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+		_ => Ok(()),
+	}
+}
+
+impl Launcher {
+	/// Whether the java of the plan reads its JVM arguments from an `@argfile`
+	/// as they are written. It is probed since a discovered or bundled runtime
+	/// may be older than the manifest requires.
+	pub(crate) async fn supports_argfiles(&self, plan: &LaunchPlan) -> bool {
+		// `javaw` prints nothing, its sibling `java` is the same runtime
+		let executable = plan.executable.with_file_name(PROBE_EXECUTABLE_NAME);
+
+		let mut discovery = JavaDiscovery::new()
+			.with_cache_path(self.root.join("cache").join("java.json"))
+			.await;
+
+		match discovery.probe(&executable).await {
+			Ok(installation) => {
+				if let Err(err) = discovery.save().await {
+					warn!(?err, "Failed to save java cache");
+				}
+
+				let major_version = installation.major_version;
+
+				// Older versions read the file in the platform charset
+				major_version >= ARGFILE_UTF8_JAVA
+					|| major_version >= ARGFILE_MIN_JAVA && plan.jvm_args.iter().all(|it| it.is_ascii())
+			}
+			Err(err) => {
+				warn!(
+					?err,
+					"Unable to probe {:?}, passing arguments inline", executable
+				);
+				false
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn quotes_arguments() {
+		assert_eq!(argfile_quote("-Xmx2G"), r#""-Xmx2G""#);
+		assert_eq!(argfile_quote(""), r#""""#);
+		assert_eq!(argfile_quote("a b"), r#""a b""#);
+		assert_eq!(
+			argfile_quote(r"C:\Games\.minecraft\"),
+			r#""C:\\Games\\.minecraft\\""#
+		);
+		assert_eq!(argfile_quote(r#"say "hi""#), r#""say \"hi\"""#);
+		assert_eq!(argfile_quote("a\nb\tc\r\x0c"), r#""a\nb\tc\r\f""#);
+		assert_eq!(argfile_quote("# not a comment"), r##""# not a comment""##);
+	}
+}
@@ -0,0 +1,98 @@
+use {
+	crate::{
+		spec::*,
+		tracing::{
+			debug,
+			trace,
+			warn,
+		},
+		Error,
+	},
+	std::{
+		io::ErrorKind,
+		path::{
+			Path,
+			PathBuf,
+		},
+	},
+	tokio::fs,
+};
+
+/// Directory the objects of the index are laid out in by their names, `None`
+/// for indexes that are read from the objects directly
+pub fn assets_target(
+	root: &Path,
+	index_id: &str,
+	index: &AssetIndex,
+	game_dir: &Path,
+) -> Option<PathBuf> {
+	if index.map_to_resources {
+		Some(game_dir.join("resources"))
+	} else if index.is_virtual {
+		Some(root.join("assets").join("virtual").join(index_id))
+	} else {
+		None
+	}
+}
+
+/// Lays out asset objects by their names for indexes that require it and
+/// returns the directory to be used as `game_assets`, see [`assets_target`].
+///
+/// Objects are hard-linked when possible and copied otherwise, files that
+/// already have the expected size are left untouched.
+pub async fn materialize_assets(
+	root: &Path,
+	index_id: &str,
+	index: &AssetIndex,
+	game_dir: &Path,
+) -> Result<Option<PathBuf>, Error> {
+	let Some(target) = assets_target(root, index_id, index, game_dir) else {
+		return Ok(None);
+	};
+
+	debug!(?target, "Materializing {} assets", index_id);
+
+	let objects_dir = root.join("assets").join("objects");
+
+	for (name, object) in &index.objects {
+		let source = objects_dir.join(object.object_path());
+
+		// Existing files at the destination are replaced, so it must not leave
+		// the target
+		let destination = match safe_relative_path(name) {
+			Ok(path) => target.join(path),
+			Err(err) => {
+				warn!(?err, "Skipping asset {}", name);
+				continue;
+			}
+		};
+
+		if let Ok(meta) = fs::metadata(&destination).await {
+			if meta.len() == object.size {
+				continue;
+			}
+
+			fs::remove_file(&destination).await?;
+		}
+
+		if let Some(parent) = destination.parent() {
+			fs::create_dir_all(parent).await?;
+		}
+
+		trace!("{} -> {:?}", object.hash, destination);
+
+		if let Err(err) = fs::hard_link(&source, &destination).await {
+			match err.kind() {
+				ErrorKind::NotFound => {
+					warn!("Missing asset object {} ({})", name, object.hash);
+					continue;
+				}
+				_ => {
+					fs::copy(&source, &destination).await?;
+				}
+			}
+		}
+	}
+
+	Ok(Some(target))
+}
@@ -0,0 +1,376 @@
+use {
+	super::{
+		physical_memory,
+		unresolved,
+		JvmOptions,
+		LaunchTarget,
+		Launcher,
+	},
+	crate::{
+		spec::*,
+		Error,
+	},
+	std::{
+		collections::HashMap,
+		path::{
+			Path,
+			PathBuf,
+		},
+	},
+};
+
+/// Placeholders a launch can not go without
+pub const REQUIRED_PLACEHOLDERS: &[&str] = &["auth_player_name"];
+
+/// Whether the name is portable across shells, `[A-Za-z_][A-Za-z0-9_]*`
+fn is_env_key(key: &str) -> bool {
+	let mut chars = key.chars();
+
+	chars
+		.next()
+		.is_some_and(|it| it.is_ascii_alphabetic() || it == '_')
+		&& chars.all(|it| it.is_ascii_alphanumeric() || it == '_')
+}
+
+/// Configures a [`Launcher`] with typed values instead of raw placeholders
+#[derive(Debug, Clone)]
+pub struct LauncherBuilder {
+	root: PathBuf,
+	id: String,
+	vars: HashMap<String, String>,
+	features: HashMap<String, bool>,
+	resolution: Option<(u32, u32)>,
+	target: Option<LaunchTarget>,
+	quick_play_path: Option<PathBuf>,
+	jvm_options: JvmOptions,
+	java_path: Option<PathBuf>,
+	strict_placeholders: bool,
+	working_directory: Option<PathBuf>,
+	env: HashMap<String, String>,
+	removed_env: Vec<String>,
+	wrapper: Vec<String>,
+	extra_libs: Vec<PathBuf>,
+	extra_jvm_args: Vec<String>,
+	extra_game_args: Vec<String>,
+}
+
+impl LauncherBuilder {
+	pub fn new(root: impl Into<PathBuf>, id: impl Into<String>) -> Self {
+		Self {
+			root: root.into(),
+			id: id.into(),
+			vars: HashMap::new(),
+			features: HashMap::new(),
+			resolution: None,
+			target: None,
+			quick_play_path: None,
+			jvm_options: JvmOptions::default(),
+			java_path: None,
+			strict_placeholders: false,
+			working_directory: None,
+			env: HashMap::new(),
+			removed_env: Vec::new(),
+			wrapper: Vec::new(),
+			extra_libs: Vec::new(),
+			extra_jvm_args: Vec::new(),
+			extra_game_args: Vec::new(),
+		}
+	}
+
+	/// Sets a raw placeholder value, prefer the typed setters when there is one
+	pub fn with_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+		self.vars.insert(name.into(), value.into());
+		self
+	}
+
+	pub fn with_game_directory(self, game_directory: impl AsRef<Path>) -> Self {
+		let game_directory = game_directory.as_ref().to_string_lossy().into_owned();
+
+		self.with_var("game_directory", game_directory)
+	}
+
+	pub fn with_player(self, name: impl Into<String>, uuid: impl Into<String>) -> Self {
+		self
+			.with_var("auth_player_name", name)
+			.with_var("auth_uuid", uuid)
+	}
+
+	/// Access token of the session and the account type, e.g. `msa`
+	pub fn with_access_token(
+		self,
+		access_token: impl Into<String>,
+		user_type: impl Into<String>,
+	) -> Self {
+		self
+			.with_var("auth_access_token", access_token)
+			.with_var("user_type", user_type)
+	}
+
+	pub fn with_resolution(mut self, width: u32, height: u32) -> Self {
+		self.resolution = Some((width, height));
+		self
+	}
+
+	/// World, server or realm to open on start
+	pub fn with_target(mut self, target: LaunchTarget) -> Self {
+		self.target = Some(target);
+		self
+	}
+
+	/// File the game logs quick play sessions to
+	pub fn with_quick_play_path(mut self, path: impl Into<PathBuf>) -> Self {
+		self.quick_play_path = Some(path.into());
+		self
+	}
+
+	/// Initial heap size in megabytes
+	pub fn with_min_memory(mut self, megabytes: u32) -> Self {
+		self.jvm_options.min_memory = Some(megabytes);
+		self
+	}
+
+	/// Maximum heap size in megabytes
+	pub fn with_max_memory(mut self, megabytes: u32) -> Self {
+		self.jvm_options.max_memory = Some(megabytes);
+		self
+	}
+
+	/// Replaces all typed JVM settings, including the memory set before
+	pub fn with_jvm_options(mut self, jvm_options: JvmOptions) -> Self {
+		self.jvm_options = jvm_options;
+		self
+	}
+
+	pub fn with_java_path(mut self, java_path: impl Into<PathBuf>) -> Self {
+		self.java_path = Some(java_path.into());
+		self
+	}
+
+	/// Fails the build and the launch on placeholders without a value instead
+	/// of passing them to the game as is
+	pub fn with_strict_placeholders(mut self, strict: bool) -> Self {
+		self.strict_placeholders = strict;
+		self
+	}
+
+	/// Directory the game is started in, the game directory by default
+	pub fn with_working_directory(mut self, working_directory: impl Into<PathBuf>) -> Self {
+		self.working_directory = Some(working_directory.into());
+		self
+	}
+
+	/// Sets an environment variable for the game
+	pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+		self.env.insert(key.into(), value.into());
+		self
+	}
+
+	/// Keeps the game from inheriting a variable of the launcher's environment
+	pub fn without_env(mut self, key: impl Into<String>) -> Self {
+		self.removed_env.push(key.into());
+		self
+	}
+
+	/// Command prefix the game is run with, e.g. `["gamemoderun"]` or
+	/// `["nice", "-n", "5"]`
+	pub fn with_wrapper<T: Into<String>>(mut self, wrapper: impl IntoIterator<Item = T>) -> Self {
+		self.wrapper = wrapper.into_iter().map(Into::into).collect();
+		self
+	}
+
+	pub fn with_feature(mut self, name: impl Into<String>, enabled: bool) -> Self {
+		self.features.insert(name.into(), enabled);
+		self
+	}
+
+	pub fn with_extra_libs(mut self, libs: impl IntoIterator<Item = PathBuf>) -> Self {
+		self.extra_libs.extend(libs);
+		self
+	}
+
+	pub fn with_jvm_args<T: Into<String>>(mut self, args: impl IntoIterator<Item = T>) -> Self {
+		self.extra_jvm_args.extend(args.into_iter().map(Into::into));
+		self
+	}
+
+	pub fn with_game_args<T: Into<String>>(mut self, args: impl IntoIterator<Item = T>) -> Self {
+		self
+			.extra_game_args
+			.extend(args.into_iter().map(Into::into));
+		self
+	}
+
+	fn check(&self) -> Result<(), Error> {
+		for name in REQUIRED_PLACEHOLDERS {
+			let is_set = self.vars.get(*name).is_some_and(|it| !it.trim().is_empty());
+
+			if !is_set {
+				return Err(Error::InvalidLauncherConfig(format!("`{name}` is not set")));
+			}
+		}
+
+		if let Some((width, height)) = self.resolution {
+			if width == 0 || height == 0 {
+				return Err(Error::InvalidLauncherConfig(format!(
+					"Invalid resolution {width}x{height}"
+				)));
+			}
+		}
+
+		if let Some(key) = self
+			.env
+			.keys()
+			.chain(&self.removed_env)
+			.find(|it| !is_env_key(it))
+		{
+			return Err(Error::InvalidLauncherConfig(format!(
+				"Invalid environment variable name `{key}`"
+			)));
+		}
+
+		if self.wrapper.first().is_some_and(|it| it.trim().is_empty()) {
+			return Err(Error::InvalidLauncherConfig(
+				"Wrapper command can not be empty".into(),
+			));
+		}
+
+		self.jvm_options.validate(physical_memory())
+	}
+
+	/// Validates the configuration and loads the manifest. Unresolved
+	/// placeholders can be listed with [`Launcher::unresolved_placeholders`],
+	/// they fail the build and the launch in strict mode.
+	pub async fn build(self) -> Result<Launcher, Error> {
+		self.check()?;
+
+		if let Some(java_path) = &self.java_path {
+			if !tokio::fs::try_exists(java_path).await? {
+				return Err(Error::InvalidJava(java_path.clone()));
+			}
+		}
+
+		let mut launcher = Launcher::try_from_root(&self.root, &self.id).await?;
+
+		launcher.vars = self.vars;
+		launcher.features = self.features;
+		launcher.java_path = self.java_path;
+		launcher.strict_placeholders = self.strict_placeholders;
+		launcher.working_directory = self.working_directory;
+		launcher.env = self.env;
+		launcher.removed_env = self.removed_env;
+		launcher.wrapper = self.wrapper;
+		launcher.extra_libs = self.extra_libs;
+
+		if let Some((width, height)) = self.resolution {
+			launcher.set_resolution(width, height);
+		}
+
+		if let Some(target) = &self.target {
+			launcher.set_target(target)?;
+		}
+
+		if let Some(path) = &self.quick_play_path {
+			launcher.set_quick_play_path(path)?;
+		}
+
+		launcher.jvm_options = self.jvm_options;
+		launcher.extra_jvm_args.extend(self.extra_jvm_args);
+		launcher.extra_game_args.extend(self.extra_game_args);
+
+		if launcher.strict_placeholders {
+			let unresolved = launcher.unresolved_placeholders()?;
+
+			if !unresolved.is_empty() {
+				return Err(Error::UnresolvedPlaceholders(unresolved));
+			}
+		}
+
+		Ok(launcher)
+	}
+}
+
+impl Launcher {
+	pub fn builder(root: impl Into<PathBuf>, id: impl Into<String>) -> LauncherBuilder {
+		LauncherBuilder::new(root, id)
+	}
+
+	/// Placeholders used by the arguments enabled for the current platform and
+	/// features that have no value, they are passed to the game as is
+	pub fn unresolved_placeholders(&self) -> Result<Vec<String>, Error> {
+		let ctx = RuleContext::target()?.with_features(self.features.clone());
+		let (jvm_args, game_args) = self.args(&ctx, None)?;
+
+		// Only the names matter, the paths resolved on launch are not needed
+		let vars = self.placeholder_values(&self.root, &self.natives_directory(), String::new())?;
+
+		Ok(unresolved(jvm_args.iter().chain(&game_args), &vars))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MANIFEST: &str = r#"{
+		"id": "1.20",
+		"assets": "5",
+		"assetIndex": {
+			"id": "5",
+			"sha1": "0000000000000000000000000000000000000000",
+			"size": 1,
+			"totalSize": 1,
+			"url": "https://piston-meta.mojang.com/v1/packages/5.json"
+		},
+		"downloads": {
+			"client": {
+				"sha1": "0000000000000000000000000000000000000000",
+				"size": 1,
+				"url": "https://piston-data.mojang.com/v1/objects/client.jar"
+			}
+		},
+		"mainClass": "net.minecraft.client.main.Main",
+		"javaVersion": { "component": "java-runtime-gamma", "majorVersion": 17 },
+		"arguments": {
+			"game": ["--username", "${auth_player_name}", "--server", "${server_address}"],
+			"jvm": ["-cp", "${classpath}"]
+		},
+		"libraries": [],
+		"type": "release"
+	}"#;
+
+	fn root() -> PathBuf {
+		let root = std::env::temp_dir().join(format!("rsmc-test-builder-{}", std::process::id()));
+		let version_dir = root.join("versions").join("1.20");
+
+		std::fs::create_dir_all(&version_dir).unwrap();
+		std::fs::write(version_dir.join("1.20.json"), MANIFEST).unwrap();
+
+		root
+	}
+
+	#[tokio::test]
+	async fn fails_strict_build_on_unresolved_placeholders() {
+		let builder = Launcher::builder(root(), "1.20").with_var("auth_player_name", "Player");
+
+		let launcher = builder.clone().build().await.unwrap();
+
+		assert_eq!(
+			launcher.unresolved_placeholders().unwrap(),
+			["server_address"]
+		);
+
+		let result = builder.clone().with_strict_placeholders(true).build().await;
+
+		assert!(matches!(
+			result,
+			Err(Error::UnresolvedPlaceholders(it)) if it == ["server_address"]
+		));
+
+		builder
+			.with_var("server_address", "localhost")
+			.with_strict_placeholders(true)
+			.build()
+			.await
+			.unwrap();
+	}
+}
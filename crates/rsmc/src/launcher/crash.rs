@@ -0,0 +1,370 @@
+use {
+	crate::tracing::{
+		debug,
+		trace,
+	},
+	serde::Serialize,
+	std::{
+		path::{
+			Path,
+			PathBuf,
+		},
+		process::ExitStatus,
+		time::SystemTime,
+	},
+	tokio::fs,
+};
+
+/// How the game process ended
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", content = "code", rename_all = "camelCase")]
+pub enum ExitKind {
+	Normal,
+	/// Non-zero exit code
+	Failed(i32),
+	/// Killed by a signal, e.g. by the OOM killer
+	Signal(i32),
+}
+
+impl ExitKind {
+	pub fn from_status(status: &ExitStatus) -> Self {
+		#[cfg(target_family = "unix")]
+		{
+			use std::os::unix::process::ExitStatusExt;
+
+			if let Some(signal) = status.signal() {
+				return ExitKind::Signal(signal);
+			}
+		}
+
+		match status.code() {
+			Some(0) => ExitKind::Normal,
+			Some(code) => ExitKind::Failed(code),
+			None => ExitKind::Failed(-1),
+		}
+	}
+
+	pub fn is_normal(&self) -> bool {
+		*self == ExitKind::Normal
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CrashReportKind {
+	/// `crash-reports/crash-*.txt` written by the game
+	Game,
+	/// `hs_err_pid*.log` written by the JVM
+	Jvm,
+}
+
+/// Likely reason of a crash guessed from the report and the game output
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CrashCause {
+	/// Game or mods are compiled for another java version
+	WrongJavaVersion,
+	OutOfMemory,
+	/// Native libraries were not found or failed to load
+	MissingNatives,
+}
+
+const CAUSE_PATTERNS: &[(CrashCause, &str)] = &[
+	(CrashCause::WrongJavaVersion, "UnsupportedClassVersionError"),
+	(
+		CrashCause::WrongJavaVersion,
+		"compiled by a more recent version of the Java Runtime",
+	),
+	(
+		CrashCause::WrongJavaVersion,
+		"cannot be cast to class java.net.URLClassLoader",
+	),
+	(
+		CrashCause::WrongJavaVersion,
+		"Unsupported class file major version",
+	),
+	(CrashCause::OutOfMemory, "java.lang.OutOfMemoryError"),
+	(
+		CrashCause::OutOfMemory,
+		"There is insufficient memory for the Java Runtime Environment",
+	),
+	(CrashCause::OutOfMemory, "Could not reserve enough space"),
+	(CrashCause::MissingNatives, "java.lang.UnsatisfiedLinkError"),
+	(CrashCause::MissingNatives, "in java.library.path"),
+	(CrashCause::MissingNatives, "Failed to locate library"),
+];
+
+/// Causes matching any of the texts, in the order they are first found in
+/// them, each once
+pub fn crash_causes<'a>(texts: impl IntoIterator<Item = &'a str>) -> Vec<CrashCause> {
+	let mut causes = Vec::new();
+
+	for text in texts {
+		for (cause, pattern) in CAUSE_PATTERNS {
+			if text.contains(pattern) && !causes.contains(cause) {
+				causes.push(*cause);
+			}
+		}
+	}
+
+	causes
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+	pub kind: CrashReportKind,
+	pub path: PathBuf,
+	pub description: Option<String>,
+	/// Exception with its stack trace, or the problematic frame for JVM crashes
+	pub exception: Option<String>,
+	/// Entries of the mod list section, if the game is modded
+	pub mods: Vec<String>,
+	pub causes: Vec<CrashCause>,
+}
+
+impl CrashReport {
+	/// Reads a `crash-reports/crash-*.txt` file
+	pub fn parse_game(path: PathBuf, content: &str) -> Self {
+		let lines: Vec<&str> = content.lines().collect();
+
+		let description_at = lines.iter().position(|it| it.starts_with("Description: "));
+
+		let description = description_at.map(|at| lines[at]["Description: ".len()..].trim().to_owned());
+
+		// Exception follows the description after a blank line and ends with
+		// the next blank line
+		let exception = description_at.and_then(|at| {
+			let trace: Vec<_> = lines[at + 1..]
+				.iter()
+				.skip_while(|it| it.trim().is_empty())
+				.take_while(|it| !it.trim().is_empty())
+				.copied()
+				.collect();
+
+			(!trace.is_empty()).then(|| trace.join("\n"))
+		});
+
+		Self {
+			kind: CrashReportKind::Game,
+			path,
+			description,
+			exception,
+			mods: mod_list(&lines),
+			causes: crash_causes([content]),
+		}
+	}
+
+	/// Reads a `hs_err_pid*.log` file
+	pub fn parse_jvm(path: PathBuf, content: &str) -> Self {
+		let lines: Vec<&str> = content
+			.lines()
+			.take_while(|it| it.starts_with('#') || it.trim().is_empty())
+			.filter_map(|it| it.strip_prefix('#'))
+			.map(str::trim)
+			.collect();
+
+		// `# A fatal error has been detected...` or `# There is insufficient
+		// memory...` followed by the details
+		let description = match lines.iter().position(|it| it.starts_with("A fatal error")) {
+			Some(at) => lines[at + 1..].iter().find(|it| !it.is_empty()),
+			None => lines.iter().find(|it| !it.is_empty()),
+		}
+		.map(|it| it.to_string());
+
+		let exception = lines
+			.iter()
+			.skip_while(|it| !it.starts_with("Problematic frame:"))
+			.nth(1)
+			.map(|it| it.to_string());
+
+		Self {
+			kind: CrashReportKind::Jvm,
+			path,
+			description,
+			exception,
+			mods: Vec::new(),
+			causes: crash_causes([content]),
+		}
+	}
+
+	/// Looks for a report written after `since` by the game in `game_dir` or by
+	/// the JVM with the given pid in one of `dirs`
+	pub async fn find(
+		game_dir: &Path,
+		dirs: &[PathBuf],
+		pid: Option<u32>,
+		since: SystemTime,
+	) -> Option<Self> {
+		if let Some(path) = newest_file(&game_dir.join("crash-reports"), since).await {
+			debug!(?path, "Crash report found");
+
+			let content = fs::read_to_string(&path).await.ok()?;
+
+			return Some(Self::parse_game(path, &content));
+		}
+
+		let name = format!("hs_err_pid{}.log", pid?);
+
+		for dir in dirs {
+			let path = dir.join(&name);
+
+			if let Ok(content) = fs::read_to_string(&path).await {
+				debug!(?path, "JVM error log found");
+
+				return Some(Self::parse_jvm(path, &content));
+			}
+		}
+
+		None
+	}
+}
+
+/// Lines nested under the mod list entry of the system details, e.g. `Mod
+/// List:` of Forge or `Fabric Mods:` of Fabric
+fn mod_list(lines: &[&str]) -> Vec<String> {
+	const KEYS: &[&str] = &["Mod List:", "Fabric Mods:", "Loaded mods:", "Mods:"];
+
+	let indent = |line: &str| line.len() - line.trim_start().len();
+
+	let Some(at) = lines
+		.iter()
+		.position(|it| KEYS.iter().any(|key| it.trim_start().starts_with(key)))
+	else {
+		return Vec::new();
+	};
+
+	let key_indent = indent(lines[at]);
+
+	lines[at + 1..]
+		.iter()
+		.take_while(|it| !it.trim().is_empty() && indent(it) > key_indent)
+		.map(|it| it.trim().to_owned())
+		.collect()
+}
+
+async fn newest_file(dir: &Path, since: SystemTime) -> Option<PathBuf> {
+	let mut entries = fs::read_dir(dir).await.ok()?;
+	let mut newest: Option<(SystemTime, PathBuf)> = None;
+
+	while let Ok(Some(entry)) = entries.next_entry().await {
+		let Ok(modified) = entry.metadata().await.and_then(|it| it.modified()) else {
+			continue;
+		};
+
+		trace!(path = ?entry.path(), ?modified);
+
+		if modified >= since && newest.as_ref().map_or(true, |(time, _)| modified > *time) {
+			newest = Some((modified, entry.path()));
+		}
+	}
+
+	newest.map(|(_, path)| path)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GAME_REPORT: &str = "\
+---- Minecraft Crash Report ----
+// Don't be sad, have a hug! <3
+
+Time: 2024-01-01 12:00:00
+Description: Initializing game
+
+java.lang.UnsatisfiedLinkError: Failed to locate library: liblwjgl.so
+	at org.lwjgl.system.Library.loadSystem(Library.java:162)
+	at org.lwjgl.system.Library.loadSystem(Library.java:62)
+
+A detailed walkthrough of the error, its code path and all known details is as follows:
+---------------------------------------------------------------------------------------
+
+-- System Details --
+Details:
+	Minecraft Version: 1.20.1
+	Fabric Mods: 
+		fabric-api: Fabric API 0.90.0+1.20.1
+		sodium: Sodium 0.5.3
+	Launched Version: fabric-loader-0.14.22-1.20.1
+";
+
+	const JVM_REPORT: &str = "\
+#
+# A fatal error has been detected by the Java Runtime Environment:
+#
+#  SIGSEGV (0xb) at pc=0x00007f, pid=1234, tid=1235
+#
+# JRE version: OpenJDK Runtime Environment (17.0.8+7) (build 17.0.8+7)
+# Problematic frame:
+# C  [liblwjgl.so+0x1234]  Java_org_lwjgl_system_JNI_invokePV+0x10
+#
+
+---------------  S U M M A R Y ------------
+";
+
+	#[test]
+	fn parses_game_report() {
+		let report = CrashReport::parse_game("crash.txt".into(), GAME_REPORT);
+
+		assert_eq!(report.kind, CrashReportKind::Game);
+		assert_eq!(report.description.as_deref(), Some("Initializing game"));
+		assert_eq!(
+			report.exception.as_deref(),
+			Some(
+				"java.lang.UnsatisfiedLinkError: Failed to locate library: liblwjgl.so\n\tat \
+				 org.lwjgl.system.Library.loadSystem(Library.java:162)\n\tat \
+				 org.lwjgl.system.Library.loadSystem(Library.java:62)"
+			)
+		);
+		assert_eq!(
+			report.mods,
+			[
+				"fabric-api: Fabric API 0.90.0+1.20.1",
+				"sodium: Sodium 0.5.3"
+			]
+		);
+		assert_eq!(report.causes, [CrashCause::MissingNatives]);
+	}
+
+	#[test]
+	fn parses_jvm_report() {
+		let report = CrashReport::parse_jvm("hs_err_pid1234.log".into(), JVM_REPORT);
+
+		assert_eq!(report.kind, CrashReportKind::Jvm);
+		assert_eq!(
+			report.description.as_deref(),
+			Some("SIGSEGV (0xb) at pc=0x00007f, pid=1234, tid=1235")
+		);
+		assert_eq!(
+			report.exception.as_deref(),
+			Some("C  [liblwjgl.so+0x1234]  Java_org_lwjgl_system_JNI_invokePV+0x10")
+		);
+		assert!(report.mods.is_empty());
+	}
+
+	#[test]
+	fn parses_report_without_sections() {
+		let report = CrashReport::parse_game("crash.txt".into(), "garbage\n");
+
+		assert_eq!(report.description, None);
+		assert_eq!(report.exception, None);
+		assert!(report.mods.is_empty());
+		assert!(report.causes.is_empty());
+	}
+
+	#[test]
+	fn guesses_causes_in_order() {
+		let causes = crash_causes([
+			"Exception in thread \"main\" java.lang.OutOfMemoryError: Java heap space",
+			"java.lang.UnsupportedClassVersionError: compiled by a more recent version of the Java \
+			 Runtime",
+			"java.lang.OutOfMemoryError again",
+		]);
+
+		assert_eq!(
+			causes,
+			[CrashCause::OutOfMemory, CrashCause::WrongJavaVersion]
+		);
+	}
+}
@@ -0,0 +1,382 @@
+use {
+	crate::{
+		tracing::{
+			debug,
+			trace,
+			warn,
+		},
+		Error,
+	},
+	serde::{
+		Deserialize,
+		Serialize,
+	},
+	std::{
+		collections::{
+			HashMap,
+			HashSet,
+		},
+		env,
+		path::{
+			Path,
+			PathBuf,
+		},
+		time::SystemTime,
+	},
+	tokio::{
+		fs,
+		process::Command,
+	},
+};
+
+/// Executable used to probe installations, `javaw` prints nothing on Windows
+pub const PROBE_EXECUTABLE_NAME: &str = {
+	if cfg!(target_os = "windows") {
+		"java.exe"
+	} else {
+		"java"
+	}
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaInstallation {
+	pub executable: PathBuf,
+	pub version: String,
+	pub major_version: u32,
+	pub vendor: Option<String>,
+	pub arch: Option<String>,
+	pub is_64bit: bool,
+}
+
+/// Major version from `java.version`, e.g. `8` for `1.8.0_392` and `17` for
+/// `17.0.9`
+pub fn parse_major_version(version: &str) -> Option<u32> {
+	let mut parts = version.split(|it: char| !it.is_ascii_digit());
+
+	match parts.next()?.parse().ok()? {
+		1 => parts.next()?.parse().ok(),
+		major => Some(major),
+	}
+}
+
+/// Runs the executable and reads its system properties
+pub async fn probe_java(executable: &Path) -> Result<JavaInstallation, Error> {
+	trace!(?executable, "Probing java");
+
+	let output = Command::new(executable)
+		.args(["-XshowSettings:properties", "-version"])
+		.output()
+		.await?;
+
+	let stderr = String::from_utf8_lossy(&output.stderr);
+
+	let properties: HashMap<&str, &str> = stderr
+		.lines()
+		.filter_map(|line| line.split_once(" = "))
+		.map(|(key, value)| (key.trim(), value.trim()))
+		.collect();
+
+	let version = properties
+		.get("java.version")
+		.ok_or_else(|| Error::InvalidJava(executable.to_owned()))?
+		.to_string();
+
+	let major_version =
+		parse_major_version(&version).ok_or_else(|| Error::InvalidJava(executable.to_owned()))?;
+
+	let arch = properties.get("os.arch").map(|it| it.to_string());
+
+	let is_64bit = match properties.get("sun.arch.data.model") {
+		Some(model) => *model == "64",
+		None => arch.as_deref().is_some_and(|it| it.contains("64")),
+	};
+
+	Ok(JavaInstallation {
+		executable: executable.to_owned(),
+		version,
+		major_version,
+		vendor: properties.get("java.vendor").map(|it| it.to_string()),
+		arch,
+		is_64bit,
+	})
+}
+
+/// Name of the architecture as in [`env::consts::ARCH`], e.g. `x86_64` for
+/// `amd64` reported by java
+fn normalize_arch(arch: &str) -> &str {
+	match arch {
+		"amd64" | "x86_64" | "x64" => "x86_64",
+		"x86" | "i386" | "i486" | "i586" | "i686" => "x86",
+		"aarch64" | "arm64" => "aarch64",
+		other => other,
+	}
+}
+
+/// `0` for the architecture of the host, `1` for an unknown one or one the
+/// host runs as well, e.g. 32-bit x86 on x86_64, and `None` otherwise
+fn arch_rank(arch: Option<&str>) -> Option<u8> {
+	let Some(arch) = arch else {
+		return Some(1);
+	};
+
+	match (env::consts::ARCH, normalize_arch(arch)) {
+		(host, arch) if host == arch => Some(0),
+		("x86_64", "x86") => Some(1),
+		// Rosetta and the Windows x64 emulation
+		("aarch64", "x86_64") if cfg!(any(target_os = "macos", target_os = "windows")) => Some(1),
+		_ => None,
+	}
+}
+
+fn home_dir() -> Option<PathBuf> {
+	env::var_os(if cfg!(target_os = "windows") {
+		"USERPROFILE"
+	} else {
+		"HOME"
+	})
+	.map(PathBuf::from)
+}
+
+/// Directories that usually contain several java homes
+fn java_roots() -> Vec<PathBuf> {
+	let mut roots: Vec<PathBuf> = Vec::new();
+
+	if cfg!(target_os = "linux") {
+		roots.extend(
+			[
+				"/usr/lib/jvm",
+				"/usr/lib64/jvm",
+				"/usr/java",
+				"/opt/java",
+				"/opt/jdk",
+			]
+			.map(PathBuf::from),
+		);
+	}
+
+	if cfg!(target_os = "macos") {
+		roots.push("/Library/Java/JavaVirtualMachines".into());
+	}
+
+	if cfg!(target_os = "windows") {
+		for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+			if let Some(program_files) = env::var_os(var).map(PathBuf::from) {
+				roots.extend(
+					[
+						"Java",
+						"Eclipse Adoptium",
+						"Zulu",
+						"Microsoft",
+						"BellSoft",
+						"Amazon Corretto",
+					]
+					.map(|it| program_files.join(it)),
+				);
+			}
+		}
+	}
+
+	if let Some(home) = home_dir() {
+		roots.push(home.join(".sdkman").join("candidates").join("java"));
+		roots.push(home.join(".asdf").join("installs").join("java"));
+		roots.push(home.join(".jdks"));
+
+		if cfg!(target_os = "macos") {
+			roots.push(home.join("Library/Java/JavaVirtualMachines"));
+		}
+	}
+
+	roots
+}
+
+fn executable_in(java_home: &Path) -> PathBuf {
+	let home = if cfg!(target_os = "macos") && java_home.join("Contents").join("Home").is_dir() {
+		java_home.join("Contents").join("Home")
+	} else {
+		java_home.to_owned()
+	};
+
+	home.join("bin").join(PROBE_EXECUTABLE_NAME)
+}
+
+/// Paths of java executables found in well-known locations
+pub async fn java_candidates() -> Vec<PathBuf> {
+	let mut candidates = Vec::new();
+
+	if let Some(java_home) = env::var_os("JAVA_HOME") {
+		candidates.push(executable_in(Path::new(&java_home)));
+	}
+
+	if let Some(path) = env::var_os("PATH") {
+		candidates.extend(env::split_paths(&path).map(|it| it.join(PROBE_EXECUTABLE_NAME)));
+	}
+
+	for root in java_roots() {
+		let Ok(mut entries) = fs::read_dir(&root).await else {
+			continue;
+		};
+
+		while let Ok(Some(entry)) = entries.next_entry().await {
+			candidates.push(executable_in(&entry.path()));
+		}
+	}
+
+	let mut seen = HashSet::new();
+	let mut unique = Vec::new();
+
+	for candidate in candidates {
+		let Ok(canonical) = fs::canonicalize(&candidate).await else {
+			continue;
+		};
+
+		if seen.insert(canonical.clone()) {
+			unique.push(canonical);
+		}
+	}
+
+	unique
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedInstallation {
+	modified: Option<SystemTime>,
+	installation: JavaInstallation,
+}
+
+/// Discovers java installations, keeping probe results until the executable
+/// changes
+#[derive(Debug, Clone, Default)]
+pub struct JavaDiscovery {
+	pub cache_path: Option<PathBuf>,
+	cache: HashMap<PathBuf, CachedInstallation>,
+}
+
+impl JavaDiscovery {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Loads previously probed installations from the file and stores new
+	/// results there
+	pub async fn with_cache_path(mut self, cache_path: impl Into<PathBuf>) -> Self {
+		let cache_path = cache_path.into();
+
+		if let Ok(content) = fs::read_to_string(&cache_path).await {
+			match serde_json::from_str(&content) {
+				Ok(cache) => self.cache = cache,
+				Err(err) => warn!(?err, "Ignoring corrupted java cache"),
+			}
+		}
+
+		self.cache_path = Some(cache_path);
+		self
+	}
+
+	pub async fn probe(&mut self, executable: &Path) -> Result<JavaInstallation, Error> {
+		let modified = fs::metadata(executable).await?.modified().ok();
+
+		if let Some(cached) = self.cache.get(executable) {
+			if cached.modified == modified {
+				return Ok(cached.installation.clone());
+			}
+		}
+
+		let installation = probe_java(executable).await?;
+
+		self.cache.insert(
+			executable.to_owned(),
+			CachedInstallation {
+				modified,
+				installation: installation.clone(),
+			},
+		);
+
+		Ok(installation)
+	}
+
+	pub async fn discover(&mut self) -> Vec<JavaInstallation> {
+		let mut installations = Vec::new();
+
+		for candidate in java_candidates().await {
+			match self.probe(&candidate).await {
+				Ok(it) => installations.push(it),
+				Err(err) => debug!(?err, ?candidate, "Not a java installation"),
+			}
+		}
+
+		if let Err(err) = self.save().await {
+			warn!(?err, "Failed to save java cache");
+		}
+
+		installations
+	}
+
+	/// Installation of exactly the given major version the host can run,
+	/// preferring its own architecture
+	pub async fn find(&mut self, major_version: u32) -> Option<JavaInstallation> {
+		self.select(major_version, false).await
+	}
+
+	/// Same as [`JavaDiscovery::find`], falling back to the closest newer
+	/// version, which not every game version runs on
+	pub async fn find_or_newer(&mut self, major_version: u32) -> Option<JavaInstallation> {
+		self.select(major_version, true).await
+	}
+
+	async fn select(&mut self, major_version: u32, allow_newer: bool) -> Option<JavaInstallation> {
+		self
+			.discover()
+			.await
+			.into_iter()
+			.filter(|it| {
+				it.major_version == major_version || allow_newer && it.major_version > major_version
+			})
+			.filter_map(|it| Some((arch_rank(it.arch.as_deref())?, it)))
+			.min_by_key(|(rank, it)| (it.major_version, *rank))
+			.map(|(_, it)| it)
+	}
+
+	pub(crate) async fn save(&self) -> Result<(), Error> {
+		let Some(cache_path) = &self.cache_path else {
+			return Ok(());
+		};
+
+		if let Some(parent) = cache_path.parent() {
+			fs::create_dir_all(parent).await?;
+		}
+
+		fs::write(cache_path, serde_json::to_string(&self.cache)?).await?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_major_versions() {
+		assert_eq!(parse_major_version("1.8.0_392"), Some(8));
+		assert_eq!(parse_major_version("17.0.9"), Some(17));
+		assert_eq!(parse_major_version("21"), Some(21));
+		assert_eq!(parse_major_version("21-ea"), Some(21));
+		assert_eq!(parse_major_version("unknown"), None);
+	}
+
+	#[test]
+	fn ranks_architectures() {
+		let host = env::consts::ARCH;
+
+		assert_eq!(arch_rank(Some(host)), Some(0));
+		assert_eq!(arch_rank(None), Some(1));
+		assert_eq!(arch_rank(Some("sparcv9")), None);
+
+		if host == "x86_64" {
+			assert_eq!(arch_rank(Some("amd64")), Some(0));
+			assert_eq!(arch_rank(Some("i386")), Some(1));
+			assert_eq!(arch_rank(Some("aarch64")), None);
+		}
+	}
+}
@@ -0,0 +1,379 @@
+use {
+	crate::Error,
+	serde::{
+		Deserialize,
+		Serialize,
+	},
+	std::collections::{
+		BTreeMap,
+		HashMap,
+		HashSet,
+	},
+};
+
+/// Heaps from this size get the larger regions of Aikar's flags
+const LARGE_HEAP_MEGABYTES: u32 = 12 * 1024;
+
+/// Memory left to the system when checking the heap against physical memory
+const RESERVED_MEGABYTES: u64 = 512;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum GarbageCollector {
+	G1,
+	/// Needs java 15, or 11 with experimental options unlocked
+	Z,
+	Shenandoah,
+	Parallel,
+	Serial,
+}
+
+impl GarbageCollector {
+	pub fn flag(&self) -> &'static str {
+		match self {
+			GarbageCollector::G1 => "-XX:+UseG1GC",
+			GarbageCollector::Z => "-XX:+UseZGC",
+			GarbageCollector::Shenandoah => "-XX:+UseShenandoahGC",
+			GarbageCollector::Parallel => "-XX:+UseParallelGC",
+			GarbageCollector::Serial => "-XX:+UseSerialGC",
+		}
+	}
+}
+
+/// Well known sets of tuning flags
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum JvmPreset {
+	/// G1 tuning by Aikar, see <https://docs.papermc.io/paper/aikars-flags>
+	Aikar,
+}
+
+impl JvmPreset {
+	pub fn args(&self, max_memory: Option<u32>) -> Vec<String> {
+		match self {
+			JvmPreset::Aikar => {
+				let large = max_memory.is_some_and(|it| it >= LARGE_HEAP_MEGABYTES);
+				let (new_size, max_new_size, region_size, reserve, occupancy) = if large {
+					(40, 50, "16M", 15, 20)
+				} else {
+					(30, 40, "8M", 20, 15)
+				};
+
+				[
+					"-XX:+UseG1GC".to_owned(),
+					"-XX:+ParallelRefProcEnabled".to_owned(),
+					"-XX:MaxGCPauseMillis=200".to_owned(),
+					"-XX:+UnlockExperimentalVMOptions".to_owned(),
+					"-XX:+DisableExplicitGC".to_owned(),
+					"-XX:+AlwaysPreTouch".to_owned(),
+					format!("-XX:G1NewSizePercent={new_size}"),
+					format!("-XX:G1MaxNewSizePercent={max_new_size}"),
+					format!("-XX:G1HeapRegionSize={region_size}"),
+					format!("-XX:G1ReservePercent={reserve}"),
+					"-XX:G1HeapWastePercent=5".to_owned(),
+					"-XX:G1MixedGCCountTarget=4".to_owned(),
+					format!("-XX:InitiatingHeapOccupancyPercent={occupancy}"),
+					"-XX:G1MixedGCLiveThresholdPercent=90".to_owned(),
+					"-XX:G1RSetUpdatingPauseTimePercent=5".to_owned(),
+					"-XX:SurvivorRatio=32".to_owned(),
+					"-XX:+PerfDisableSharedMem".to_owned(),
+					"-XX:MaxTenuringThreshold=1".to_owned(),
+				]
+				.into()
+			}
+		}
+	}
+
+	/// Collector the preset is tuned for
+	pub fn garbage_collector(&self) -> GarbageCollector {
+		match self {
+			JvmPreset::Aikar => GarbageCollector::G1,
+		}
+	}
+}
+
+/// Typed JVM settings of a launch, rendered after the manifest's own arguments
+/// so they take precedence
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JvmOptions {
+	/// Initial heap size in megabytes
+	pub min_memory: Option<u32>,
+	/// Maximum heap size in megabytes
+	pub max_memory: Option<u32>,
+	pub garbage_collector: Option<GarbageCollector>,
+	pub preset: Option<JvmPreset>,
+	pub large_pages: bool,
+	/// System properties passed as `-D<name>=<value>`
+	pub properties: BTreeMap<String, String>,
+}
+
+impl JvmOptions {
+	/// Checks the heap sizes against each other and against `physical_memory`
+	/// in bytes, when it is known
+	pub fn validate(&self, physical_memory: Option<u64>) -> Result<(), Error> {
+		let invalid = |message: String| Err(Error::InvalidLauncherConfig(message));
+
+		match (self.min_memory, self.max_memory) {
+			(_, Some(0)) | (Some(0), _) => return invalid("Memory can not be zero".into()),
+			(Some(min), Some(max)) if min > max => {
+				return invalid(format!(
+					"Initial memory {min}M is greater than maximum {max}M"
+				))
+			}
+			_ => {}
+		}
+
+		if let (Some(physical), Some(heap)) = (physical_memory, self.max_memory.or(self.min_memory)) {
+			let available = (physical / 1024 / 1024).saturating_sub(RESERVED_MEGABYTES);
+
+			if u64::from(heap) > available {
+				return invalid(format!(
+					"Heap of {heap}M does not fit into {available}M of available memory"
+				));
+			}
+		}
+
+		if let (Some(preset), Some(gc)) = (self.preset, self.garbage_collector) {
+			if preset.garbage_collector() != gc {
+				return invalid(format!("{preset:?} preset can not be used with {gc:?} GC"));
+			}
+		}
+
+		if let Some(name) = self
+			.properties
+			.keys()
+			.find(|it| it.is_empty() || it.contains(['=', ' ']))
+		{
+			return invalid(format!("Invalid system property name `{name}`"));
+		}
+
+		Ok(())
+	}
+
+	pub fn to_args(&self) -> Vec<String> {
+		let mut args = Vec::new();
+
+		if let Some(min) = self.min_memory {
+			args.push(format!("-Xms{min}M"));
+		}
+
+		if let Some(max) = self.max_memory {
+			args.push(format!("-Xmx{max}M"));
+		}
+
+		if let Some(gc) = self.garbage_collector {
+			args.push(gc.flag().to_owned());
+		}
+
+		if let Some(preset) = self.preset {
+			args.extend(preset.args(self.max_memory));
+		}
+
+		if self.large_pages {
+			args.push("-XX:+UseLargePages".into());
+		}
+
+		args.extend(
+			self
+				.properties
+				.iter()
+				.map(|(name, value)| format!("-D{name}={value}")),
+		);
+
+		args
+	}
+}
+
+/// Flags selecting the garbage collector, only one of them can be enabled
+const GC_SELECTORS: &[&str] = &[
+	"UseSerialGC",
+	"UseParallelGC",
+	"UseParallelOldGC",
+	"UseConcMarkSweepGC",
+	"UseG1GC",
+	"UseZGC",
+	"UseShenandoahGC",
+	"UseEpsilonGC",
+];
+
+/// Option an argument sets, arguments with the same key override each other
+fn jvm_arg_key(arg: &str) -> Option<String> {
+	const SIZES: &[&str] = &["-Xms", "-Xmx", "-Xss", "-Xmn"];
+
+	if let Some(flag) = arg.strip_prefix("-XX:") {
+		let name = flag
+			.trim_start_matches(['+', '-'])
+			.split('=')
+			.next()
+			.unwrap_or_default();
+
+		// Only one collector can be selected, disabling one selects nothing
+		if flag.starts_with('+') && GC_SELECTORS.contains(&name) {
+			return Some("gc".into());
+		}
+
+		return Some(format!("XX:{name}"));
+	}
+
+	if let Some(property) = arg.strip_prefix("-D") {
+		let name = property.split('=').next().unwrap_or_default();
+
+		return Some(format!("D:{name}"));
+	}
+
+	SIZES
+		.iter()
+		.find(|it| arg.starts_with(*it))
+		.map(|it| it.to_string())
+}
+
+/// Whether the option unlocks others, which only work after it
+fn is_unlock_key(key: &str) -> bool {
+	key.starts_with("XX:Unlock")
+}
+
+/// Drops JVM options overridden by a later argument, other arguments are kept
+/// as is. Unlock options keep their first position with the last value, so
+/// the options they unlock still follow them.
+pub fn dedup_jvm_args(args: Vec<String>) -> Vec<String> {
+	let mut last = HashMap::new();
+
+	for (index, arg) in args.iter().enumerate() {
+		if let Some(key) = jvm_arg_key(arg) {
+			last.insert(key, index);
+		}
+	}
+
+	let mut unlocked = HashSet::new();
+	let mut deduped = Vec::with_capacity(args.len());
+
+	for (index, arg) in args.iter().enumerate() {
+		match jvm_arg_key(arg) {
+			None => deduped.push(arg.clone()),
+			Some(key) if is_unlock_key(&key) => {
+				if unlocked.insert(key.clone()) {
+					deduped.push(args[last[&key]].clone());
+				}
+			}
+			Some(key) if last[&key] == index => deduped.push(arg.clone()),
+			Some(_) => {}
+		}
+	}
+
+	deduped
+}
+
+/// Total physical memory in bytes
+#[cfg(target_family = "unix")]
+pub fn physical_memory() -> Option<u64> {
+	// SAFETY: `sysconf` only reads system configuration
+	let (pages, page_size) = unsafe {
+		(
+			libc::sysconf(libc::_SC_PHYS_PAGES),
+			libc::sysconf(libc::_SC_PAGESIZE),
+		)
+	};
+
+	(pages > 0 && page_size > 0).then(|| pages as u64 * page_size as u64)
+}
+
+/// Total physical memory in bytes
+#[cfg(target_family = "windows")]
+pub fn physical_memory() -> Option<u64> {
+	use windows_sys::Win32::System::SystemInformation::{
+		GlobalMemoryStatusEx,
+		MEMORYSTATUSEX,
+	};
+
+	// SAFETY: the structure is plain data and its size is set as required
+	unsafe {
+		let mut status: MEMORYSTATUSEX = std::mem::zeroed();
+		status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+
+		(GlobalMemoryStatusEx(&mut status) != 0).then_some(status.ullTotalPhys)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn dedup(args: &[&str]) -> Vec<String> {
+		dedup_jvm_args(args.iter().map(|it| it.to_string()).collect())
+	}
+
+	#[test]
+	fn keeps_last_option() {
+		assert_eq!(
+			dedup(&[
+				"-Xmx2G",
+				"-Dfoo=1",
+				"-cp",
+				"a.jar",
+				"-XX:+UseG1GC",
+				"-Xmx4096M",
+				"-Dfoo=2",
+				"-XX:+UseZGC",
+				"-XX:-AlwaysPreTouch",
+				"-XX:+AlwaysPreTouch",
+			]),
+			[
+				"-cp",
+				"a.jar",
+				"-Xmx4096M",
+				"-Dfoo=2",
+				"-XX:+UseZGC",
+				"-XX:+AlwaysPreTouch"
+			]
+		);
+	}
+
+	#[test]
+	fn keeps_unlock_options_first() {
+		assert_eq!(
+			dedup(&[
+				"-XX:+UnlockExperimentalVMOptions",
+				"-XX:G1NewSizePercent=20",
+				"-XX:+UnlockDiagnosticVMOptions",
+				"-XX:+UnlockExperimentalVMOptions",
+				"-XX:G1NewSizePercent=30",
+			]),
+			[
+				"-XX:+UnlockExperimentalVMOptions",
+				"-XX:+UnlockDiagnosticVMOptions",
+				"-XX:G1NewSizePercent=30"
+			]
+		);
+	}
+
+	#[test]
+	fn keeps_other_arguments() {
+		let args = ["-javaagent:a.jar", "-javaagent:a.jar", "-ea", "-Xss1M"];
+
+		assert_eq!(dedup(&args), args);
+	}
+
+	#[test]
+	fn keeps_unrelated_gc_options() {
+		assert_eq!(
+			dedup(&[
+				"-XX:+UseG1GC",
+				"-XX:+PrintGCDetails",
+				"-Xlog:gc",
+				"-XX:+DisableExplicitGC",
+				"-XX:+UseMaximumCompactionOnSystemGC",
+				"-XX:-UseZGC",
+				"-XX:+UseShenandoahGC",
+			]),
+			[
+				"-XX:+PrintGCDetails",
+				"-Xlog:gc",
+				"-XX:+DisableExplicitGC",
+				"-XX:+UseMaximumCompactionOnSystemGC",
+				"-XX:-UseZGC",
+				"-XX:+UseShenandoahGC"
+			]
+		);
+	}
+}
@@ -0,0 +1,359 @@
+use {
+	serde::Serialize,
+	std::collections::HashMap,
+	tokio::sync::mpsc::{
+		self,
+		Receiver,
+	},
+};
+
+const EVENT_START: &str = "<log4j:Event";
+const EVENT_END: &str = "</log4j:Event>";
+
+/// Size an unfinished event may grow to before it is given up as plain output
+const MAX_EVENT_LEN: usize = 256 * 1024;
+
+/// Line of the game output with the stream it was written to
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "stream", content = "line", rename_all = "camelCase")]
+pub enum OutputLine {
+	Stdout(String),
+	Stderr(String),
+}
+
+impl OutputLine {
+	pub fn text(&self) -> &str {
+		match self {
+			OutputLine::Stdout(it) | OutputLine::Stderr(it) => it,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+	Trace,
+	Debug,
+	Info,
+	Warn,
+	Error,
+	Fatal,
+}
+
+impl LogLevel {
+	pub fn parse(value: &str) -> Option<Self> {
+		match value.to_ascii_uppercase().as_str() {
+			"TRACE" => Some(Self::Trace),
+			"DEBUG" => Some(Self::Debug),
+			"INFO" => Some(Self::Info),
+			"WARN" => Some(Self::Warn),
+			"ERROR" => Some(Self::Error),
+			"FATAL" => Some(Self::Fatal),
+			_ => None,
+		}
+	}
+}
+
+/// Single `<log4j:Event>` emitted by the game with the log4j2-xml config
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+	/// Milliseconds since the unix epoch
+	pub timestamp: Option<u64>,
+	pub level: LogLevel,
+	pub logger: String,
+	pub thread: String,
+	pub message: String,
+	pub throwable: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "data", rename_all = "camelCase")]
+pub enum LogEntry {
+	Record(LogRecord),
+	/// Output that is not a log4j event, e.g. JVM messages or games without
+	/// xml logging
+	Plain(String),
+}
+
+/// Line-oriented parser of the game output
+#[derive(Debug, Default)]
+pub struct LogParser {
+	event: Option<String>,
+}
+
+impl LogParser {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Feeds a line of output, returning an entry once it is complete
+	pub fn push(&mut self, line: &str) -> Option<LogEntry> {
+		let event = match self.event.take() {
+			Some(mut event) => {
+				event.push_str(line);
+				event
+			}
+			None if line.trim_start().starts_with(EVENT_START) => line.to_owned(),
+			None => return Some(LogEntry::Plain(line.trim_end().to_owned())),
+		};
+
+		if event.contains(EVENT_END) {
+			return Some(
+				parse_event(&event)
+					.map(LogEntry::Record)
+					.unwrap_or_else(|| LogEntry::Plain(event.trim_end().to_owned())),
+			);
+		}
+
+		// Output that only looks like an event must not be buffered forever
+		if event.len() > MAX_EVENT_LEN {
+			return Some(LogEntry::Plain(event.trim_end().to_owned()));
+		}
+
+		self.event = Some(event);
+		None
+	}
+
+	/// Returns an unfinished event as plain output
+	pub fn finish(self) -> Option<LogEntry> {
+		self
+			.event
+			.map(|it| LogEntry::Plain(it.trim_end().to_owned()))
+	}
+}
+
+/// Parses output lines received from the launched game in the background.
+/// Only stdout carries log4j events, stderr is passed through as plain output.
+pub fn parse_output(mut rx: Receiver<OutputLine>) -> Receiver<LogEntry> {
+	let (tx, parsed) = mpsc::channel(64);
+
+	tokio::spawn(async move {
+		let mut parser = LogParser::new();
+
+		while let Some(line) = rx.recv().await {
+			let entry = match line {
+				OutputLine::Stdout(line) => parser.push(&line),
+				OutputLine::Stderr(line) => Some(LogEntry::Plain(line.trim_end().to_owned())),
+			};
+
+			if let Some(entry) = entry {
+				if tx.send(entry).await.is_err() {
+					return;
+				}
+			}
+		}
+
+		if let Some(entry) = parser.finish() {
+			let _ = tx.send(entry).await;
+		}
+	});
+
+	parsed
+}
+
+fn parse_event(event: &str) -> Option<LogRecord> {
+	let start = event.find(EVENT_START)? + EVENT_START.len();
+	let end = start + event[start..].find('>')?;
+	let attributes = parse_attributes(&event[start..end]);
+
+	Some(LogRecord {
+		timestamp: attributes.get("timestamp").and_then(|it| it.parse().ok()),
+		level: LogLevel::parse(attributes.get("level")?)?,
+		logger: attributes.get("logger").cloned().unwrap_or_default(),
+		thread: attributes.get("thread").cloned().unwrap_or_default(),
+		message: element_text(event, "log4j:Message").unwrap_or_default(),
+		throwable: element_text(event, "log4j:Throwable"),
+	})
+}
+
+fn parse_attributes(tag: &str) -> HashMap<String, String> {
+	let mut attributes = HashMap::new();
+	let mut rest = tag;
+
+	while let Some(eq) = rest.find('=') {
+		let key = rest[..eq].trim();
+		let value = rest[eq + 1..].trim_start();
+
+		let Some(quote) = value.chars().next().filter(|it| *it == '"' || *it == '\'') else {
+			break;
+		};
+
+		let Some(len) = value[1..].find(quote) else {
+			break;
+		};
+
+		attributes.insert(key.to_owned(), unescape(&value[1..=len]));
+		rest = &value[len + 2..];
+	}
+
+	attributes
+}
+
+fn element_text(event: &str, name: &str) -> Option<String> {
+	let open = format!("<{name}>");
+	let close = format!("</{name}>");
+
+	let start = event.find(&open)? + open.len();
+	let end = start + event[start..].find(&close)?;
+
+	Some(text(&event[start..end]))
+}
+
+/// Character data of an element, mixing `CDATA` sections and escaped text
+fn text(mut content: &str) -> String {
+	const CDATA_START: &str = "<![CDATA[";
+	const CDATA_END: &str = "]]>";
+
+	let mut out = String::new();
+
+	while let Some(start) = content.find(CDATA_START) {
+		out.push_str(&unescape(content[..start].trim()));
+
+		let data = &content[start + CDATA_START.len()..];
+		let end = data.find(CDATA_END).unwrap_or(data.len());
+
+		out.push_str(&data[..end]);
+		content = data.get(end + CDATA_END.len()..).unwrap_or_default();
+	}
+
+	out.push_str(&unescape(content.trim()));
+	out
+}
+
+fn unescape(value: &str) -> String {
+	let mut out = String::with_capacity(value.len());
+	let mut rest = value;
+
+	while let Some(amp) = rest.find('&') {
+		out.push_str(&rest[..amp]);
+		rest = &rest[amp..];
+
+		let Some(semi) = rest.find(';') else {
+			break;
+		};
+
+		let entity = &rest[1..semi];
+		let decoded = match entity {
+			"lt" => Some('<'),
+			"gt" => Some('>'),
+			"amp" => Some('&'),
+			"quot" => Some('"'),
+			"apos" => Some('\''),
+			_ => entity
+				.strip_prefix("#x")
+				.map(|it| u32::from_str_radix(it, 16))
+				.or_else(|| entity.strip_prefix('#').map(str::parse))
+				.and_then(Result::ok)
+				.and_then(char::from_u32),
+		};
+
+		match decoded {
+			Some(it) => {
+				out.push(it);
+				rest = &rest[semi + 1..];
+			}
+			None => {
+				out.push('&');
+				rest = &rest[1..];
+			}
+		}
+	}
+
+	out.push_str(rest);
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn record(entry: Option<LogEntry>) -> LogRecord {
+		match entry {
+			Some(LogEntry::Record(it)) => it,
+			other => panic!("Expected a record, got {other:?}"),
+		}
+	}
+
+	fn plain(entry: Option<LogEntry>) -> String {
+		match entry {
+			Some(LogEntry::Plain(it)) => it,
+			other => panic!("Expected plain output, got {other:?}"),
+		}
+	}
+
+	#[test]
+	fn parses_event_split_across_lines() {
+		let mut parser = LogParser::new();
+
+		let lines = [
+			"<log4j:Event logger=\"net.minecraft.client.Minecraft\" timestamp=\"1700000000000\" level=\"INFO\" thread=\"Render thread\">\n",
+			"  <log4j:Message><![CDATA[Setting user: Player &amp; co]]></log4j:Message>\n",
+		];
+
+		for line in lines {
+			assert!(parser.push(line).is_none());
+		}
+
+		let record = record(parser.push("</log4j:Event>\n"));
+
+		assert_eq!(record.level, LogLevel::Info);
+		assert_eq!(record.timestamp, Some(1700000000000));
+		assert_eq!(record.logger, "net.minecraft.client.Minecraft");
+		assert_eq!(record.thread, "Render thread");
+		assert_eq!(record.message, "Setting user: Player &amp; co");
+		assert_eq!(record.throwable, None);
+		assert!(parser.finish().is_none());
+	}
+
+	#[test]
+	fn joins_nested_cdata_sections() {
+		let mut parser = LogParser::new();
+
+		let record = record(parser.push(concat!(
+			"<log4j:Event logger=\"a\" level=\"WARN\" thread=\"main\">",
+			"<log4j:Message><![CDATA[a]]]]><![CDATA[>b]]></log4j:Message>",
+			"<log4j:Throwable>x &lt; y</log4j:Throwable>",
+			"</log4j:Event>",
+		)));
+
+		assert_eq!(record.message, "a]]>b");
+		assert_eq!(record.throwable.as_deref(), Some("x < y"));
+	}
+
+	#[test]
+	fn passes_garbage_through() {
+		let mut parser = LogParser::new();
+
+		assert_eq!(
+			plain(parser.push("[12:00:00] plain text\n")),
+			"[12:00:00] plain text"
+		);
+		assert_eq!(
+			plain(parser.push("<log4j:Event level=\"LOUD\"></log4j:Event>")),
+			"<log4j:Event level=\"LOUD\"></log4j:Event>"
+		);
+		assert_eq!(
+			plain(parser.push("<log4j:Event level=</log4j:Event>")),
+			"<log4j:Event level=</log4j:Event>"
+		);
+
+		assert!(parser.push("<log4j:Event level=\"INFO\">\n").is_none());
+		assert_eq!(plain(parser.finish()), "<log4j:Event level=\"INFO\">");
+	}
+
+	#[test]
+	fn flushes_oversized_event() {
+		let mut parser = LogParser::new();
+		let line = "x".repeat(1024) + "\n";
+
+		assert!(parser.push("<log4j:Event level=\"INFO\">\n").is_none());
+
+		let flushed = std::iter::repeat(line.as_str())
+			.take(MAX_EVENT_LEN / line.len() + 2)
+			.find_map(|it| parser.push(it));
+
+		assert!(plain(flushed).starts_with(EVENT_START));
+		assert!(parser.finish().is_none());
+	}
+}
@@ -16,23 +16,50 @@ use {
 			PathBuf,
 		},
 		sync::Arc,
+		time::SystemTime,
 	},
 	tokio::{
-		fs::File,
 		io::{
 			AsyncBufReadExt,
-			AsyncReadExt,
+			AsyncRead,
 			BufReader,
 		},
-		process::Command,
 		sync::{
 			mpsc,
-			mpsc::Receiver,
+			mpsc::Sender,
 		},
 		task::JoinHandle,
 	},
 };
 
+mod argfile;
+mod assets;
+mod builder;
+mod crash;
+mod java;
+mod jvm;
+mod log;
+mod natives;
+mod plan;
+mod process;
+mod server;
+mod target;
+
+pub use {
+	argfile::*,
+	assets::*,
+	builder::*,
+	crash::*,
+	java::*,
+	jvm::*,
+	log::*,
+	natives::*,
+	plan::*,
+	process::*,
+	server::*,
+	target::*,
+};
+
 pub const EXECUTABLE_NAME: &str = {
 	if cfg!(target_os = "windows") {
 		"javaw.exe"
@@ -48,12 +75,24 @@ pub struct Launcher {
 	pub extra_libs: Vec<PathBuf>,
 	pub extra_jvm_args: Vec<String>,
 	pub extra_game_args: Vec<String>,
+	pub jvm_options: JvmOptions,
 	pub vars: HashMap<String, String>,
+	pub features: HashMap<String, bool>,
+	/// Java executable to use instead of the bundled runtime
+	pub java_path: Option<PathBuf>,
+	/// Fail the launch on placeholders without a value
+	pub strict_placeholders: bool,
+	/// Directory the game is started in instead of the game directory
+	pub working_directory: Option<PathBuf>,
+	/// Variables set for the game on top of the launcher's environment
+	pub env: HashMap<String, String>,
+	/// Variables of the launcher's environment the game does not inherit
+	pub removed_env: Vec<String>,
+	/// Command prefix the game is run with, e.g. `gamemoderun` or `nice -n 5`
+	pub wrapper: Vec<String>,
 }
 
-type LauncherProcessHandle = (JoinHandle<()>, JoinHandle<()>, Receiver<String>);
-
-fn process_args(args: Vec<Argument>, to: &mut Vec<String>) {
+fn process_args(args: Vec<Argument>, ctx: &RuleContext, to: &mut Vec<String>) {
 	for arg in args {
 		match arg {
 			Argument::Constant(it) => {
@@ -61,7 +100,7 @@ fn process_args(args: Vec<Argument>, to: &mut Vec<String>) {
 				to.push(it)
 			}
 			Argument::Conditional { rules, value } => {
-				if Rule::unpack_all(&rules) {
+				if Rule::unpack_all(&rules, ctx) {
 					match value {
 						ConditionalArgument::Single(it) => {
 							trace!("+arg: {}", it);
@@ -88,219 +127,219 @@ fn set_vars(vars: &HashMap<String, String>, target: &str) -> String {
 	target
 }
 
-impl Launcher {
-	pub async fn try_from_root(root: &Path, id: &String) -> Result<Self, Error> {
-		let manifest_path = root.join("versions").join(id).join(format!("{}.json", &id));
-
-		debug!("Reading manifest from {:?}", manifest_path);
+/// Bundled runtime of the version in the root or a discovered installation of
+/// the required version, preferring `executable_name` in its `bin` directory
+pub(crate) async fn find_java(
+	root: &Path,
+	java_version: &JavaVersion,
+	executable_name: &str,
+) -> Result<PathBuf, Error> {
+	let JavaVersion {
+		component,
+		major_version,
+		..
+	} = java_version;
+
+	let jre_dir = root.join("jre").join(component);
+	let jre_home = if cfg!(target_os = "macos") {
+		jre_dir.join("jre.bundle").join("Contents").join("Home")
+	} else {
+		jre_dir
+	};
 
-		let mut file = File::open(manifest_path).await?;
-		let mut content = String::new();
-		file.read_to_string(&mut content).await?;
+	let bundled = jre_home.join("bin").join(executable_name);
 
-		let manifest: Manifest = serde_json::from_str(&content)?;
+	if tokio::fs::try_exists(&bundled).await? {
+		#[cfg(target_family = "unix")]
+		{
+			use std::{
+				fs::Permissions,
+				os::unix::fs::PermissionsExt,
+			};
 
-		let manifest = match manifest {
-			Manifest::Native(it) => it,
-			Manifest::Inherited(_) => return Err(Error::Inherited),
-		};
+			trace!("Set permissions");
 
-		debug!(?id, "Manifest loaded");
+			tokio::fs::set_permissions(&bundled, Permissions::from_mode(0o744)).await?
+		}
 
-		Ok(Self {
-			root: root.to_owned(),
-			manifest,
-			extra_libs: Vec::new(),
-			extra_game_args: Vec::new(),
-			extra_jvm_args: Vec::new(),
-			vars: HashMap::new(),
-		})
+		return Ok(bundled);
 	}
 
-	pub async fn launch(self) -> Result<LauncherProcessHandle, Error> {
-		let mut classpath: Vec<PathBuf> = Vec::new();
+	debug!(
+		?bundled,
+		"Bundled runtime is missing, looking for java {}", major_version
+	);
 
-		let assets_dir = self.root.join("assets");
-		let libraries_dir = self.root.join("libraries");
-		let version_dir = self.root.join("versions").join(&self.manifest.id);
-		let jre_dir = self
-			.root
-			.join("jre")
-			.join(&self.manifest.java_version.component);
+	let installation = JavaDiscovery::new()
+		.with_cache_path(root.join("cache").join("java.json"))
+		.await
+		.find(*major_version)
+		.await
+		.ok_or(Error::JavaNotFound(*major_version))?;
 
-		trace!(?version_dir, ?jre_dir, ?assets_dir, ?libraries_dir);
+	debug!(?installation, "Using discovered java");
 
-		for lib in self.manifest.libraries {
-			use Library::*;
+	let preferred = installation.executable.with_file_name(executable_name);
 
-			match lib {
-				Custom(it) => {
-					trace!("Including (custom) : {}", it.name);
-					classpath.push(it.name.to_path());
-				}
-				Common(it) => {
-					trace!("Including         : {}", it.name);
-					classpath.push(it.downloads.artifact.path);
-				}
-				Seminative(it) => {
-					if Rule::unpack_all(&it.rules) {
-						trace!("Including (native): {}", it.name);
-						classpath.push(it.downloads.artifact.path);
-					}
-				}
-				Native(mut it) => {
-					trace!(?it);
-					if Rule::unpack_all(&it.rules) {
-						let classifier = it.natives.get_classifier_name()?;
-						let artifact = it.downloads.extract_artifact(classifier)?;
-
-						trace!("Including (base)  : {}", it.name);
-						classpath.push(it.downloads.artifact.path);
+	if tokio::fs::try_exists(&preferred).await? {
+		Ok(preferred)
+	} else {
+		Ok(installation.executable)
+	}
+}
 
-						trace!("Including (native): {}", it.name);
-						classpath.push(artifact.path);
-					}
+/// Sends every line read from the pipe to the channel until it is closed,
+/// wrapped with `wrap`, e.g. to tag the stream it comes from
+pub(crate) fn forward_lines<R, T>(
+	pipe: R,
+	tx: Arc<Sender<T>>,
+	wrap: fn(String) -> T,
+) -> JoinHandle<()>
+where
+	R: AsyncRead + Unpin + Send + 'static,
+	T: Send + 'static,
+{
+	tokio::spawn(async move {
+		let mut buf = BufReader::new(pipe);
+
+		loop {
+			let mut out = String::new();
+
+			if let Ok(bytes) = buf.read_line(&mut out).await {
+				let is_empty = out.is_empty();
+
+				if bytes == 0 && is_empty {
+					break;
 				}
-			};
-		}
-
-		let args = match self.manifest.arguments {
-			ArgsContainer::Modern(it) => it,
-			ArgsContainer::Legacy(it) => it.into(),
-		};
-
-		let mut jvm_args = Vec::new();
-		let mut game_args = Vec::new();
 
-		process_args(args.arguments.jvm, &mut jvm_args);
-		process_args(args.arguments.game, &mut game_args);
+				let _ = tx.send(wrap(out)).await;
+			}
+		}
+	})
+}
 
-		jvm_args.extend(self.extra_jvm_args);
-		game_args.extend(self.extra_game_args);
+impl Launcher {
+	pub async fn try_from_root(root: &Path, id: &String) -> Result<Self, Error> {
+		let manifest = resolve_manifest(root, id).await?;
 
-		// This is intentional! Do not remove without RFC
-		jvm_args.retain(|it| !it.starts_with("-Djava.library.path"));
+		debug!(?id, "Manifest loaded");
 
-		let mut classpath: Vec<_> = classpath
-			.into_iter()
-			.map(|it| libraries_dir.join(it))
-			.collect();
+		Ok(Self {
+			root: root.to_owned(),
+			manifest,
+			extra_libs: Vec::new(),
+			extra_game_args: Vec::new(),
+			extra_jvm_args: Vec::new(),
+			jvm_options: JvmOptions::default(),
+			vars: HashMap::new(),
+			features: HashMap::new(),
+			java_path: None,
+			strict_placeholders: false,
+			working_directory: None,
+			env: HashMap::new(),
+			removed_env: Vec::new(),
+			wrapper: Vec::new(),
+		})
+	}
 
-		classpath.push(version_dir.join(format!("{}.jar", &self.manifest.id)));
+	pub fn set_feature(&mut self, name: impl Into<String>, enabled: bool) {
+		self.features.insert(name.into(), enabled);
+	}
 
-		classpath.extend(self.extra_libs);
+	/// Enables the `has_custom_resolution` feature and provides the
+	/// `resolution_*` variables for the game window
+	pub fn set_resolution(&mut self, width: u32, height: u32) {
+		self.set_feature(features::CUSTOM_RESOLUTION, true);
+		self
+			.vars
+			.insert("resolution_width".into(), width.to_string());
+		self
+			.vars
+			.insert("resolution_height".into(), height.to_string());
+	}
 
-		let cp_items: Vec<_> = classpath
-			.into_iter()
-			.map(|it| {
-				it.to_str()
-					.ok_or(Error::InvalidUtf8Path)
-					.map(|it| it.to_owned())
-			})
-			.collect::<Result<_, _>>()?;
+	/// Java executable for the launch: the explicit override, the bundled
+	/// runtime or a discovered installation of the required version
+	pub async fn java_executable(&self) -> Result<PathBuf, Error> {
+		match &self.java_path {
+			Some(java_path) => Ok(java_path.clone()),
+			None => find_java(&self.root, &self.manifest.java_version, EXECUTABLE_NAME).await,
+		}
+	}
 
-		trace!(?cp_items, "Classpath");
+	pub async fn launch(self) -> Result<GameProcess, Error> {
+		let natives_dir = self.launch_natives_directory();
+		let plan = self.prepare_with_natives(natives_dir.clone()).await?;
+		trace!("Launch plan:\n{}", plan);
 
-		let cp_string = cp_items.join(if cfg!(target_os = "windows") {
-			";"
-		} else {
-			":"
-		});
+		self.prepare_files().await?;
 
-		macro vars($($name:ident:$value:expr,)*) {
-			HashMap::from([
-				$((stringify!($name).into(), { $value }.into()),)*
-			])
+		if let Err(err) = self.extract_natives(&natives_dir).await {
+			let _ = remove_natives(&natives_dir).await;
+			return Err(err);
 		}
 
-		let mut vars: HashMap<String, String> = vars! {
-			version_name: self.manifest.id,
-			version_type: self.manifest.version_type,
-			assets_index_name: self.manifest.assets,
-			assets_root: assets_dir.to_str().ok_or(Error::InvalidUtf8Path)?,
-			natives_directory: version_dir.join("natives").to_str().ok_or(Error::InvalidUtf8Path)?,
-			classpath: cp_string,
+		let argfile_dir = if self.supports_argfiles(&plan).await {
+			match plan.write_argfile().await {
+				Ok(dir) => Some(dir),
+				Err(err) => {
+					let _ = remove_natives(&natives_dir).await;
+					return Err(err);
+				}
+			}
+		} else {
+			None
 		};
 
-		vars.extend(self.vars);
-
-		let executable = jre_dir.join("bin").join(EXECUTABLE_NAME);
-		trace!(?executable);
-
-		#[cfg(target_family = "unix")]
-		{
-			use std::{
-				fs::Permissions,
-				os::unix::fs::PermissionsExt,
-			};
-
-			trace!("Set permissions");
-
-			tokio::fs::set_permissions(&executable, Permissions::from_mode(0o744)).await?
-		}
-
-		let mut command = Command::new(executable);
+		let mut command = match &argfile_dir {
+			Some(dir) => plan.command_with_argfile(&argfile_path(dir)),
+			None => plan.command(),
+		};
 
 		command.stdout(Stdio::piped());
 		command.stderr(Stdio::piped());
 
-		command.args(jvm_args.into_iter().map(|it| set_vars(&vars, &it)));
-		command.arg(self.manifest.main_class);
-		command.args(game_args.into_iter().map(|it| set_vars(&vars, &it)));
+		let started_at = SystemTime::now();
+		let mut process = match command.spawn() {
+			Ok(process) => process,
+			Err(err) => {
+				if let Some(dir) = &argfile_dir {
+					let _ = remove_argfile(dir).await;
+				}
 
-		trace!(?command);
+				let _ = remove_natives(&natives_dir).await;
 
-		let process = command.spawn()?;
+				return Err(err.into());
+			}
+		};
 		trace!("Spawned");
 
-		let (tx, rx) = mpsc::channel::<String>(64);
+		let (tx, rx) = mpsc::channel(64);
 
 		let tx = Arc::new(tx);
 
-		let stdout_task = tokio::spawn({
-			let pipe = process.stdout.unwrap();
-			let tx = tx.clone();
-			let mut buf = BufReader::new(pipe);
-
-			async move {
-				loop {
-					let mut out = String::new();
-
-					if let Ok(bytes) = buf.read_line(&mut out).await {
-						let is_empty = out.is_empty();
-
-						if bytes == 0 && is_empty {
-							break;
-						}
-
-						let _ = tx.send(out).await;
-					}
-				}
-			}
-		});
-
-		let stderr_task = tokio::spawn({
-			let pipe = process.stderr.unwrap();
-			let tx = tx.clone();
-			let mut buf = BufReader::new(pipe);
-
-			async move {
-				loop {
-					let mut out = String::new();
-
-					if let Ok(bytes) = buf.read_line(&mut out).await {
-						let is_empty = out.is_empty();
-
-						if bytes == 0 && is_empty {
-							break;
-						}
-
-						let _ = tx.send(out).await;
-					}
-				}
-			}
-		});
-
-		Ok((stdout_task, stderr_task, rx))
+		let stdout_task = forward_lines(
+			process.stdout.take().unwrap(),
+			tx.clone(),
+			OutputLine::Stdout,
+		);
+		let stderr_task = forward_lines(
+			process.stderr.take().unwrap(),
+			tx.clone(),
+			OutputLine::Stderr,
+		);
+
+		Ok(GameProcess::new(
+			process,
+			rx,
+			vec![stdout_task, stderr_task],
+			LaunchDirs {
+				natives: natives_dir,
+				argfile: argfile_dir,
+				game: self.game_directory(),
+				working: self.working_directory(),
+			},
+			started_at,
+		))
 	}
 }
@@ -0,0 +1,165 @@
+use {
+	crate::{
+		spec::*,
+		tracing::{
+			debug,
+			trace,
+			warn,
+		},
+		Error,
+	},
+	crc32fast::Hasher,
+	std::{
+		fs::{
+			self,
+			File,
+		},
+		io::{
+			self,
+			ErrorKind,
+			Read,
+		},
+		path::{
+			Path,
+			PathBuf,
+		},
+	},
+	zip::ZipArchive,
+};
+
+/// Native jars of the libraries allowed by `ctx` with their extraction rules
+fn native_jars(
+	root: &Path,
+	manifest: &NativeManifest,
+	ctx: &RuleContext,
+) -> Result<Vec<(PathBuf, Option<ExtractRules>)>, Error> {
+	let libraries_dir = root.join("libraries");
+	let mut jars = Vec::new();
+
+	for lib in &manifest.libraries {
+		let Library::Native(it) = lib else {
+			continue;
+		};
+
+		if !Rule::unpack_all(&it.rules, ctx) {
+			continue;
+		}
+
+		let classifier = it.natives.get_classifier_name(ctx)?;
+		let artifact = it
+			.downloads
+			.classifiers
+			.get(&classifier)
+			.ok_or(Error::InvalidManifest("Missing native classifier".into()))?;
+
+		jars.push((libraries_dir.join(&artifact.path), it.extract.clone()));
+	}
+
+	Ok(jars)
+}
+
+/// Whether the manifest has native jars to extract on the platform
+pub fn has_natives(manifest: &NativeManifest, ctx: &RuleContext) -> bool {
+	manifest.libraries.iter().any(|lib| match lib {
+		Library::Native(it) => Rule::unpack_all(&it.rules, ctx),
+		_ => false,
+	})
+}
+
+fn is_unchanged(path: &Path, size: u64, crc32: u32) -> io::Result<bool> {
+	let mut file = match File::open(path) {
+		Ok(file) => file,
+		Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+		Err(err) => return Err(err),
+	};
+
+	if file.metadata()?.len() != size {
+		return Ok(false);
+	}
+
+	let mut hasher = Hasher::new();
+	let mut buf = [0; 8192];
+
+	loop {
+		match file.read(&mut buf)? {
+			0 => break,
+			read => hasher.update(&buf[..read]),
+		}
+	}
+
+	Ok(hasher.finalize() == crc32)
+}
+
+fn extract_jar(jar: &Path, rules: Option<&ExtractRules>, dir: &Path) -> Result<(), Error> {
+	let mut archive = ZipArchive::new(File::open(jar)?)?;
+
+	for index in 0..archive.len() {
+		let mut entry = archive.by_index(index)?;
+
+		if entry.is_dir() || rules.is_some_and(|it| it.is_excluded(entry.name())) {
+			continue;
+		}
+
+		let Some(relative) = entry.enclosed_name().map(Path::to_owned) else {
+			warn!("Skipping unsafe entry {} of {:?}", entry.name(), jar);
+			continue;
+		};
+
+		let target = dir.join(relative);
+
+		if is_unchanged(&target, entry.size(), entry.crc32())? {
+			continue;
+		}
+
+		trace!("Extracting {:?}", target);
+
+		if let Some(parent) = target.parent() {
+			fs::create_dir_all(parent)?;
+		}
+
+		io::copy(&mut entry, &mut File::create(&target)?)?;
+	}
+
+	Ok(())
+}
+
+/// Unpacks native jars of the libraries allowed by `ctx` into `dir`.
+///
+/// Files that are already there with the same size and checksum are left
+/// untouched, other files of the directory are kept as well since a running
+/// game may still use them.
+pub async fn extract_natives(
+	root: &Path,
+	manifest: &NativeManifest,
+	ctx: &RuleContext,
+	dir: &Path,
+) -> Result<(), Error> {
+	let jars = native_jars(root, manifest, ctx)?;
+
+	if jars.is_empty() {
+		return Ok(());
+	}
+
+	let dir = dir.to_owned();
+
+	tokio::task::spawn_blocking(move || {
+		fs::create_dir_all(&dir)?;
+
+		for (jar, rules) in &jars {
+			debug!("Extracting natives from {:?}", jar);
+			extract_jar(jar, rules.as_ref(), &dir)?;
+		}
+
+		Ok(())
+	})
+	.await
+	.map_err(io::Error::from)?
+}
+
+/// Removes the natives directory once the game is closed
+pub async fn remove_natives(dir: &Path) -> Result<(), Error> {
+	match tokio::fs::remove_dir_all(dir).await {
+		Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+		_ => Ok(()),
+	}
+}
@@ -0,0 +1,798 @@
+use {
+	super::{
+		assets_target,
+		dedup_jvm_args,
+		extract_natives,
+		has_natives,
+		materialize_assets,
+		process_args,
+		set_vars,
+		Launcher,
+	},
+	crate::{
+		spec::*,
+		tracing::{
+			trace,
+			warn,
+		},
+		Error,
+	},
+	std::{
+		collections::{
+			BTreeMap,
+			BTreeSet,
+			HashMap,
+		},
+		fmt::{
+			Display,
+			Formatter,
+		},
+		path::{
+			Path,
+			PathBuf,
+		},
+		time::{
+			SystemTime,
+			UNIX_EPOCH,
+		},
+	},
+	tokio::{
+		fs,
+		process::Command,
+	},
+};
+
+/// Value of `launcher_name`, reported by the game in crash reports and
+/// telemetry
+pub const LAUNCHER_NAME: &str = "rsmc";
+
+pub const LAUNCHER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Placeholders holding credentials that must not end up in logs
+pub const SECRET_PLACEHOLDERS: &[&str] =
+	&["auth_access_token", "auth_session", "auth_xuid", "clientid"];
+
+/// Game arguments followed by a credential
+pub const SECRET_FLAGS: &[&str] = &["--accessToken", "--session", "--xuid", "--clientId"];
+
+const REDACTED: &str = "<redacted>";
+
+const CLASSPATH_SEPARATOR: &str = if cfg!(target_os = "windows") {
+	";"
+} else {
+	":"
+};
+
+/// Values of the launch that depend on the file system, resolved by
+/// [`Launcher::prepare`]
+#[derive(Debug, Clone)]
+pub struct LaunchInputs {
+	pub executable: PathBuf,
+	pub ctx: RuleContext,
+	/// Directory used as `game_assets`
+	pub game_assets: PathBuf,
+	/// Directory used as `natives_directory`
+	pub natives_dir: PathBuf,
+	/// Installed logging config of the version
+	pub log_config: Option<PathBuf>,
+}
+
+/// Everything needed to start the game, with all placeholders substituted
+#[derive(Debug, Clone)]
+pub struct LaunchPlan {
+	pub executable: PathBuf,
+	/// Working directory, the launcher's one when empty
+	pub cwd: Option<PathBuf>,
+	/// Variables set on top of the launcher's environment
+	pub env: BTreeMap<String, String>,
+	/// Variables of the launcher's environment the game does not inherit
+	pub removed_env: Vec<String>,
+	/// Command the java executable is run with, e.g. `gamemoderun`
+	pub wrapper: Vec<String>,
+	pub jvm_args: Vec<String>,
+	pub main_class: String,
+	pub game_args: Vec<String>,
+	pub classpath: Vec<PathBuf>,
+	secrets: Vec<String>,
+}
+
+impl Launcher {
+	/// Directory the game keeps its saves and options in
+	pub fn game_directory(&self) -> PathBuf {
+		self
+			.vars
+			.get("game_directory")
+			.map(PathBuf::from)
+			.unwrap_or_else(|| self.root.clone())
+	}
+
+	/// Directory the game is started in
+	pub fn working_directory(&self) -> PathBuf {
+		self
+			.working_directory
+			.clone()
+			.unwrap_or_else(|| self.game_directory())
+	}
+
+	/// Shared natives directory of the version, used by plans made with
+	/// [`Launcher::prepare`]
+	pub fn natives_directory(&self) -> PathBuf {
+		self
+			.root
+			.join("versions")
+			.join(&self.manifest.id)
+			.join("natives")
+	}
+
+	/// New natives directory for a single launch, so a game exiting never
+	/// removes the natives of another one still running
+	pub(crate) fn launch_natives_directory(&self) -> PathBuf {
+		let nanos = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|it| it.as_nanos())
+			.unwrap_or_default();
+
+		self
+			.root
+			.join("versions")
+			.join(&self.manifest.id)
+			.join(format!("natives-{}-{nanos}", std::process::id()))
+	}
+
+	/// Unpacks the native jars enabled for the current platform and features
+	/// into `dir`
+	pub async fn extract_natives(&self, dir: &Path) -> Result<(), Error> {
+		let ctx = RuleContext::target()?.with_features(self.features.clone());
+
+		extract_natives(&self.root, &self.manifest, &ctx, dir).await
+	}
+
+	/// Creates the directories the game expects and lays out the assets of
+	/// legacy indexes, the files a plan from [`Launcher::prepare`] relies on
+	/// besides the natives
+	pub async fn prepare_files(&self) -> Result<(), Error> {
+		fs::create_dir_all(self.working_directory()).await?;
+
+		let quick_play = self.features.get(features::QUICK_PLAYS_SUPPORT) == Some(&true);
+
+		if let Some(parent) = self
+			.vars
+			.get("quickPlayPath")
+			.filter(|_| quick_play)
+			.and_then(|it| Path::new(it).parent())
+		{
+			fs::create_dir_all(parent).await?;
+		}
+
+		match read_asset_index(&self.root, &self.manifest.assets).await {
+			Ok(index) => {
+				materialize_assets(
+					&self.root,
+					&self.manifest.assets,
+					&index,
+					&self.game_directory(),
+				)
+				.await?;
+			}
+			Err(err) => warn!(?err, "Unable to read asset index {}", self.manifest.assets),
+		}
+
+		Ok(())
+	}
+
+	/// Resolves the java executable and plans the launch as a dry run, nothing
+	/// is written. The plan uses the natives in [`Launcher::natives_directory`]
+	/// and expects [`Launcher::extract_natives`] and
+	/// [`Launcher::prepare_files`] to be run before it.
+	pub async fn prepare(&self) -> Result<LaunchPlan, Error> {
+		self.prepare_with_natives(self.natives_directory()).await
+	}
+
+	/// Same as [`Launcher::prepare`] with the natives in `natives_dir`
+	pub(crate) async fn prepare_with_natives(
+		&self,
+		natives_dir: PathBuf,
+	) -> Result<LaunchPlan, Error> {
+		let executable = self.java_executable().await?;
+		trace!(?executable);
+
+		let ctx = RuleContext::target()?.with_features(self.features.clone());
+		trace!(?ctx);
+
+		let game_assets = match read_asset_index(&self.root, &self.manifest.assets).await {
+			Ok(index) => assets_target(
+				&self.root,
+				&self.manifest.assets,
+				&index,
+				&self.game_directory(),
+			),
+			Err(err) => {
+				warn!(?err, "Unable to read asset index {}", self.manifest.assets);
+				None
+			}
+		}
+		.unwrap_or_else(|| self.root.join("assets"));
+
+		let log_config = match &self.manifest.logging {
+			Some(Logging { client, .. }) => {
+				let path = log_config_path(&self.root, &client.file);
+
+				if fs::try_exists(&path).await? {
+					Some(path)
+				} else {
+					warn!("Logging config {} is not installed", client.file.id);
+					None
+				}
+			}
+			None => None,
+		};
+
+		self.plan(&LaunchInputs {
+			executable,
+			ctx,
+			game_assets,
+			natives_dir,
+			log_config,
+		})
+	}
+
+	/// JVM and game arguments enabled by `ctx` with the launcher's own ones,
+	/// placeholders are not substituted yet
+	pub(crate) fn args(
+		&self,
+		ctx: &RuleContext,
+		log_config: Option<&Path>,
+	) -> Result<(Vec<String>, Vec<String>), Error> {
+		let args = self.manifest.arguments.clone().into_modern();
+
+		let mut jvm_args = Vec::new();
+		let mut game_args = Vec::new();
+
+		process_args(args.arguments.jvm, ctx, &mut jvm_args);
+		process_args(args.arguments.game, ctx, &mut game_args);
+
+		if let (Some(Logging { client, .. }), Some(path)) = (&self.manifest.logging, log_config) {
+			let path = path.to_str().ok_or(Error::InvalidUtf8Path)?;
+
+			trace!("Logging config: {}", path);
+			jvm_args.push(client.argument.replace("${path}", path));
+		}
+
+		if has_natives(&self.manifest, ctx) {
+			// `java.library.path` is not passed, so libraries are pointed to the
+			// natives directly
+			jvm_args.extend([
+				"-Dorg.lwjgl.librarypath=${natives_directory}".into(),
+				"-Dnet.java.games.input.librarypath=${natives_directory}".into(),
+			]);
+		}
+
+		jvm_args.extend(self.jvm_options.to_args());
+		jvm_args.extend(self.extra_jvm_args.iter().cloned());
+		game_args.extend(self.extra_game_args.iter().cloned());
+
+		let mut jvm_args = dedup_jvm_args(jvm_args);
+
+		// This is intentional! Do not remove without RFC
+		jvm_args.retain(|it| !it.starts_with("-Djava.library.path"));
+
+		Ok((jvm_args, game_args))
+	}
+
+	/// Values of all placeholders: the defaults of the official launcher and
+	/// the paths of the root, overridden by [`Launcher::vars`]
+	pub(crate) fn placeholder_values(
+		&self,
+		game_assets: &Path,
+		natives_dir: &Path,
+		classpath: String,
+	) -> Result<HashMap<String, String>, Error> {
+		let assets_dir = self.root.join("assets");
+		let libraries_dir = self.root.join("libraries");
+		let game_dir = self.game_directory();
+
+		macro vars($($name:ident:$value:expr,)*) {
+			HashMap::from([
+				$((stringify!($name).into(), { $value }.into()),)*
+			])
+		}
+
+		let mut vars: HashMap<String, String> = vars! {
+			version_name: self.manifest.id.as_str(),
+			version_type: self.manifest.version_type.as_str(),
+			assets_index_name: self.manifest.assets.as_str(),
+			assets_root: assets_dir.to_str().ok_or(Error::InvalidUtf8Path)?,
+			game_assets: game_assets.to_str().ok_or(Error::InvalidUtf8Path)?,
+			natives_directory: natives_dir.to_str().ok_or(Error::InvalidUtf8Path)?,
+			library_directory: libraries_dir.to_str().ok_or(Error::InvalidUtf8Path)?,
+			game_directory: game_dir.to_str().ok_or(Error::InvalidUtf8Path)?,
+			classpath: classpath,
+			classpath_separator: CLASSPATH_SEPARATOR,
+			launcher_name: LAUNCHER_NAME,
+			launcher_version: LAUNCHER_VERSION,
+			resolution_width: "854",
+			resolution_height: "480",
+			user_type: "legacy",
+			user_properties: "{}",
+			auth_xuid: "",
+			clientid: "",
+		};
+
+		vars.extend(self.vars.clone());
+
+		Ok(vars)
+	}
+
+	/// Builds the command line without touching the file system
+	pub fn plan(&self, inputs: &LaunchInputs) -> Result<LaunchPlan, Error> {
+		let LaunchInputs {
+			executable,
+			ctx,
+			game_assets,
+			natives_dir,
+			log_config,
+		} = inputs;
+
+		let mut classpath: Vec<PathBuf> = Vec::new();
+
+		let libraries_dir = self.root.join("libraries");
+		let version_dir = self.root.join("versions").join(&self.manifest.id);
+		let client_jar = {
+			let jar_id = self.manifest.jar_id();
+
+			self
+				.root
+				.join("versions")
+				.join(jar_id)
+				.join(format!("{jar_id}.jar"))
+		};
+		trace!(?version_dir, ?natives_dir, ?libraries_dir);
+
+		for lib in &self.manifest.libraries {
+			use Library::*;
+
+			match lib {
+				Custom(it) => {
+					trace!("Including (custom) : {}", it.name);
+					classpath.push(it.name.to_file_path());
+				}
+				Common(it) => {
+					trace!("Including         : {}", it.name);
+					classpath.push(it.downloads.artifact.path.clone());
+				}
+				Seminative(it) => {
+					if Rule::unpack_all(&it.rules, ctx) {
+						trace!("Including (native): {}", it.name);
+						classpath.push(it.downloads.artifact.path.clone());
+					}
+				}
+				Native(it) => {
+					trace!(?it);
+					if Rule::unpack_all(&it.rules, ctx) {
+						let classifier = it.natives.get_classifier_name(ctx)?;
+						let artifact = it
+							.downloads
+							.classifiers
+							.get(&classifier)
+							.ok_or(Error::InvalidManifest("Missing native classifier".into()))?;
+
+						if let Some(base) = &it.downloads.artifact {
+							trace!("Including (base)  : {}", it.name);
+							classpath.push(base.path.clone());
+						}
+
+						trace!("Including (native): {}", it.name);
+						classpath.push(artifact.path.clone());
+					}
+				}
+			};
+		}
+
+		let (jvm_args, game_args) = self.args(ctx, log_config.as_deref())?;
+
+		let mut classpath: Vec<_> = classpath
+			.into_iter()
+			.map(|it| libraries_dir.join(it))
+			.collect();
+
+		classpath.push(client_jar);
+
+		classpath.extend(self.extra_libs.iter().cloned());
+
+		let cp_items: Vec<_> = classpath
+			.iter()
+			.map(|it| {
+				it.to_str()
+					.ok_or(Error::InvalidUtf8Path)
+					.map(|it| it.to_owned())
+			})
+			.collect::<Result<_, _>>()?;
+
+		trace!(?cp_items, "Classpath");
+
+		let vars =
+			self.placeholder_values(game_assets, natives_dir, cp_items.join(CLASSPATH_SEPARATOR))?;
+
+		let unresolved = unresolved(jvm_args.iter().chain(&game_args), &vars);
+
+		if !unresolved.is_empty() {
+			if self.strict_placeholders {
+				return Err(Error::UnresolvedPlaceholders(unresolved));
+			}
+
+			warn!(?unresolved, "Placeholders are passed to the game as is");
+		}
+
+		let secrets = SECRET_PLACEHOLDERS
+			.iter()
+			.filter_map(|name| vars.get(*name))
+			.filter(|it| !it.is_empty())
+			.cloned()
+			.collect();
+
+		Ok(LaunchPlan {
+			executable: executable.clone(),
+			cwd: Some(self.working_directory()),
+			env: self
+				.env
+				.iter()
+				.map(|(key, value)| (key.clone(), value.clone()))
+				.collect(),
+			removed_env: self.removed_env.clone(),
+			wrapper: self.wrapper.clone(),
+			jvm_args: jvm_args.iter().map(|it| set_vars(&vars, it)).collect(),
+			main_class: self.manifest.main_class.clone(),
+			game_args: game_args.iter().map(|it| set_vars(&vars, it)).collect(),
+			classpath,
+			secrets,
+		})
+	}
+}
+
+/// Placeholders of the arguments without a value, sorted and deduplicated
+pub(crate) fn unresolved<'a>(
+	args: impl IntoIterator<Item = &'a String>,
+	vars: &HashMap<String, String>,
+) -> Vec<String> {
+	let unresolved: BTreeSet<_> = args
+		.into_iter()
+		.flat_map(|it| placeholders(it))
+		.filter(|it| !vars.contains_key(*it))
+		.map(str::to_owned)
+		.collect();
+
+	unresolved.into_iter().collect()
+}
+
+fn shell_quote(value: &str) -> String {
+	format!("'{}'", value.replace('\'', r#"'"'"'"#))
+}
+
+/// Quotes a value for a batch file. Double quotes can not be escaped in a way
+/// both cmd and the java launcher understand, so values with them are
+/// rejected.
+fn batch_quote(value: &str) -> Result<String, Error> {
+	if value.contains('"') {
+		return Err(Error::InvalidLauncherConfig(
+			"Values with double quotes can not be written to a batch file".into(),
+		));
+	}
+
+	// Backslashes right before the closing quote would escape it
+	let trailing = value.len() - value.trim_end_matches('\\').len();
+
+	Ok(format!(
+		"\"{}{}\"",
+		value.replace('%', "%%"),
+		"\\".repeat(trailing)
+	))
+}
+
+impl LaunchPlan {
+	/// Wrapper and executable followed by all of their arguments
+	pub fn command_line(&self) -> Vec<String> {
+		let mut line = self.wrapper.clone();
+
+		line.push(self.executable.to_string_lossy().into_owned());
+
+		line.extend(self.jvm_args.iter().cloned());
+		line.push(self.main_class.clone());
+		line.extend(self.game_args.iter().cloned());
+
+		line
+	}
+
+	/// Copy of the plan with every argument holding a credential or following
+	/// a [`SECRET_FLAGS`] flag replaced as a whole. Environment values are
+	/// replaced as well, they often carry credentials the launcher does not
+	/// know about.
+	pub fn redacted(&self) -> Self {
+		let redact = |args: &[String]| -> Vec<String> {
+			let mut follows_flag = false;
+
+			args
+				.iter()
+				.map(|arg| {
+					let is_secret = follows_flag
+						|| self
+							.secrets
+							.iter()
+							.any(|secret| arg.contains(secret.as_str()));
+
+					follows_flag = SECRET_FLAGS.contains(&arg.as_str());
+
+					if is_secret {
+						REDACTED.to_owned()
+					} else {
+						arg.clone()
+					}
+				})
+				.collect()
+		};
+
+		Self {
+			env: self
+				.env
+				.keys()
+				.map(|key| (key.clone(), REDACTED.to_owned()))
+				.collect(),
+			jvm_args: redact(&self.jvm_args),
+			game_args: redact(&self.game_args),
+			secrets: Vec::new(),
+			..self.clone()
+		}
+	}
+
+	/// Command running the executable, through the wrapper if there is one,
+	/// without any arguments of the executable
+	pub(crate) fn base_command(&self) -> Command {
+		let mut command = match self.wrapper.split_first() {
+			Some((program, args)) => {
+				let mut command = Command::new(program);
+
+				command.args(args);
+				command.arg(&self.executable);
+				command
+			}
+			None => Command::new(&self.executable),
+		};
+
+		if let Some(cwd) = &self.cwd {
+			command.current_dir(cwd);
+		}
+
+		for key in &self.removed_env {
+			command.env_remove(key);
+		}
+
+		command.envs(&self.env);
+		command
+	}
+
+	pub fn command(&self) -> Command {
+		let mut command = self.base_command();
+
+		command.args(&self.jvm_args);
+		command.arg(&self.main_class);
+		command.args(&self.game_args);
+
+		command
+	}
+
+	/// POSIX shell script running the same command, credentials included
+	pub fn to_shell_script(&self) -> String {
+		let mut script = String::from("#!/bin/sh\n\n");
+
+		if let Some(cwd) = &self.cwd {
+			script += &format!("cd {} || exit 1\n", shell_quote(&cwd.to_string_lossy()));
+		}
+
+		for key in &self.removed_env {
+			script += &format!("unset {key}\n");
+		}
+
+		for (key, value) in &self.env {
+			script += &format!("export {key}={}\n", shell_quote(value));
+		}
+
+		let line: Vec<_> = self
+			.command_line()
+			.iter()
+			.map(|it| shell_quote(it))
+			.collect();
+
+		script += &format!("\nexec {}\n", line.join(" \\\n\t"));
+		script
+	}
+
+	/// Windows batch file running the same command, credentials included
+	pub fn to_batch_script(&self) -> Result<String, Error> {
+		let mut script = String::from("@echo off\r\n\r\n");
+
+		if let Some(cwd) = &self.cwd {
+			script += &format!("cd /d {}\r\n", batch_quote(&cwd.to_string_lossy())?);
+		}
+
+		for key in &self.removed_env {
+			script += &format!("set \"{key}=\"\r\n");
+		}
+
+		for (key, value) in &self.env {
+			if value.contains('"') {
+				return Err(Error::InvalidLauncherConfig(format!(
+					"Value of {key} can not be written to a batch file"
+				)));
+			}
+
+			script += &format!("set \"{key}={}\"\r\n", value.replace('%', "%%"));
+		}
+
+		let line: Vec<_> = self
+			.command_line()
+			.iter()
+			.map(|it| batch_quote(it))
+			.collect::<Result<_, _>>()?;
+
+		script += &format!("\r\n{}\r\n", line.join(" ^\r\n\t"));
+		Ok(script)
+	}
+
+	pub async fn write_shell_script(&self, path: &Path) -> Result<(), Error> {
+		fs::write(path, self.to_shell_script()).await?;
+
+		#[cfg(target_family = "unix")]
+		{
+			use std::{
+				fs::Permissions,
+				os::unix::fs::PermissionsExt,
+			};
+
+			fs::set_permissions(path, Permissions::from_mode(0o755)).await?;
+		}
+
+		Ok(())
+	}
+
+	pub async fn write_batch_script(&self, path: &Path) -> Result<(), Error> {
+		fs::write(path, self.to_batch_script()?).await?;
+
+		Ok(())
+	}
+}
+
+impl Display for LaunchPlan {
+	/// Human-readable plan with credentials redacted
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let plan = self.redacted();
+
+		writeln!(f, "Executable: {}", plan.executable.display())?;
+
+		if let Some(cwd) = &plan.cwd {
+			writeln!(f, "Working directory: {}", cwd.display())?;
+		}
+
+		for (key, value) in &plan.env {
+			writeln!(f, "Environment: {key}={value}")?;
+		}
+
+		for key in &plan.removed_env {
+			writeln!(f, "Removed from environment: {key}")?;
+		}
+
+		if !plan.wrapper.is_empty() {
+			writeln!(f, "Wrapper: {}", plan.wrapper.join(" "))?;
+		}
+
+		// The classpath argument is listed by its entries below
+		let classpath: Vec<_> = plan
+			.classpath
+			.iter()
+			.map(|it| it.to_string_lossy())
+			.collect();
+		let classpath = classpath.join(CLASSPATH_SEPARATOR);
+
+		writeln!(f, "JVM arguments:")?;
+		for arg in &plan.jvm_args {
+			if !plan.classpath.is_empty() && *arg == classpath {
+				writeln!(f, "\t<classpath>")?;
+			} else {
+				writeln!(f, "\t{arg}")?;
+			}
+		}
+
+		writeln!(f, "Main class: {}", plan.main_class)?;
+
+		writeln!(f, "Game arguments:")?;
+		for arg in &plan.game_args {
+			writeln!(f, "\t{arg}")?;
+		}
+
+		writeln!(f, "Classpath:")?;
+		for entry in &plan.classpath {
+			writeln!(f, "\t{}", entry.display())?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn plan(game_args: &[&str]) -> LaunchPlan {
+		LaunchPlan {
+			executable: "java".into(),
+			cwd: None,
+			env: BTreeMap::from([("API_KEY".into(), "hunter2".into())]),
+			removed_env: Vec::new(),
+			wrapper: Vec::new(),
+			jvm_args: vec!["-Dtoken=abc".into(), "-Xmx2G".into()],
+			main_class: "net.minecraft.client.main.Main".into(),
+			game_args: game_args.iter().map(|it| it.to_string()).collect(),
+			classpath: Vec::new(),
+			secrets: vec!["abc".into()],
+		}
+	}
+
+	#[test]
+	fn redacts_whole_arguments() {
+		let redacted = plan(&[
+			"--username",
+			"Player",
+			"--accessToken",
+			"ab",
+			"--session",
+			"token:abc:uuid",
+			"--xuid",
+			"",
+		])
+		.redacted();
+
+		assert_eq!(redacted.jvm_args, [REDACTED, "-Xmx2G"]);
+		assert_eq!(
+			redacted.game_args,
+			[
+				"--username",
+				"Player",
+				"--accessToken",
+				REDACTED,
+				"--session",
+				REDACTED,
+				"--xuid",
+				REDACTED
+			]
+		);
+		assert_eq!(redacted.env["API_KEY"], REDACTED);
+
+		let shown = plan(&[]).to_string();
+
+		assert!(!shown.contains("abc"));
+		assert!(!shown.contains("hunter2"));
+	}
+
+	#[test]
+	fn quotes_batch_values() {
+		assert_eq!(batch_quote("C:\\Games\\").unwrap(), "\"C:\\Games\\\\\"");
+		assert_eq!(batch_quote("100%").unwrap(), "\"100%%\"");
+		assert!(batch_quote("a\"b").is_err());
+
+		let mut with_quote = plan(&["--title", "\"x\""]);
+		assert!(with_quote.to_batch_script().is_err());
+
+		with_quote.game_args.clear();
+		assert!(with_quote.to_batch_script().is_ok());
+	}
+
+	#[test]
+	fn displays_classpath_once() {
+		let mut plan = plan(&[]);
+		plan.classpath = vec!["a.jar".into(), "b.jar".into()];
+		plan.jvm_args = vec!["-cp".into(), format!("a.jar{CLASSPATH_SEPARATOR}b.jar")];
+
+		let display = plan.to_string();
+
+		assert!(display.contains("\t-cp\n\t<classpath>\n"));
+		assert_eq!(display.matches("a.jar").count(), 1);
+	}
+}
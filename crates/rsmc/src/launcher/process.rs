@@ -0,0 +1,252 @@
+use {
+	super::{
+		crash_causes,
+		remove_argfile,
+		remove_natives,
+		CrashCause,
+		CrashReport,
+		ExitKind,
+		OutputLine,
+	},
+	crate::{
+		tracing::{
+			debug,
+			warn,
+		},
+		Error,
+	},
+	serde::Serialize,
+	std::{
+		collections::VecDeque,
+		path::PathBuf,
+		process::ExitStatus,
+		sync::{
+			Arc,
+			Mutex,
+		},
+		time::{
+			Duration,
+			SystemTime,
+		},
+	},
+	tokio::{
+		process::Child,
+		sync::{
+			mpsc,
+			mpsc::Receiver,
+		},
+		task::JoinHandle,
+	},
+};
+
+/// Lines of output kept to guess the crash cause from
+const TAIL_LINES: usize = 200;
+
+/// Outcome of a game session
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameExit {
+	pub kind: ExitKind,
+	/// Report of a game that stopped with a failure on its own
+	pub crash: Option<CrashReport>,
+	/// Likely causes found in the crash report and the game output
+	pub causes: Vec<CrashCause>,
+}
+
+/// Directories of a launch the process cleans up or looks for reports in
+#[derive(Debug, Clone)]
+pub(crate) struct LaunchDirs {
+	pub natives: PathBuf,
+	pub argfile: Option<PathBuf>,
+	pub game: PathBuf,
+	/// Directory the game is started in, where the JVM writes its error log
+	pub working: PathBuf,
+}
+
+/// Running game, the natives and argfile directories are removed once it exits
+#[derive(Debug)]
+pub struct GameProcess {
+	child: Child,
+	pid: Option<u32>,
+	output: Option<Receiver<OutputLine>>,
+	output_tasks: Vec<JoinHandle<()>>,
+	tail: Arc<Mutex<VecDeque<String>>>,
+	dirs: LaunchDirs,
+	started_at: SystemTime,
+	/// Stopped by the launcher, so the exit is not a crash
+	stopped: bool,
+}
+
+impl GameProcess {
+	pub(crate) fn new(
+		child: Child,
+		mut output: Receiver<OutputLine>,
+		mut output_tasks: Vec<JoinHandle<()>>,
+		dirs: LaunchDirs,
+		started_at: SystemTime,
+	) -> Self {
+		let tail = Arc::new(Mutex::new(VecDeque::with_capacity(TAIL_LINES)));
+		let (tx, rx) = mpsc::channel(64);
+
+		output_tasks.push(tokio::spawn({
+			let tail = tail.clone();
+
+			async move {
+				while let Some(line) = output.recv().await {
+					if let Ok(mut tail) = tail.lock() {
+						if tail.len() == TAIL_LINES {
+							tail.pop_front();
+						}
+
+						tail.push_back(line.text().to_owned());
+					}
+
+					// A full channel means the output is not taken or read too
+					// slowly, the line is dropped so the game never blocks on
+					// its pipes
+					let _ = tx.try_send(line);
+				}
+			}
+		}));
+
+		Self {
+			pid: child.id(),
+			child,
+			output: Some(rx),
+			output_tasks,
+			tail,
+			dirs,
+			started_at,
+			stopped: false,
+		}
+	}
+
+	/// Id of the process, kept after it exits
+	pub fn pid(&self) -> Option<u32> {
+		self.pid
+	}
+
+	/// Lines of stdout and stderr, can only be taken once
+	pub fn take_output(&mut self) -> Option<Receiver<OutputLine>> {
+		self.output.take()
+	}
+
+	/// Waits for the game to exit and all of its output to be read
+	pub async fn wait(&mut self) -> Result<ExitStatus, Error> {
+		let status = self.child.wait().await?;
+		debug!(pid = self.pid, "Game exited with {}", status);
+
+		for task in self.output_tasks.drain(..) {
+			if let Err(err) = task.await {
+				warn!(?err, "Output reader failed");
+			}
+		}
+
+		if let Err(err) = remove_natives(&self.dirs.natives).await {
+			warn!(?err, "Unable to remove natives");
+		}
+
+		if let Some(dir) = &self.dirs.argfile {
+			if let Err(err) = remove_argfile(dir).await {
+				warn!(?err, "Unable to remove argfile");
+			}
+		}
+
+		Ok(status)
+	}
+
+	/// Waits for the game to exit and collects the crash report if it failed
+	pub async fn wait_for_exit(&mut self) -> Result<GameExit, Error> {
+		let status = self.wait().await?;
+		let kind = ExitKind::from_status(&status);
+
+		if kind.is_normal() || self.stopped {
+			return Ok(GameExit {
+				kind,
+				crash: None,
+				causes: Vec::new(),
+			});
+		}
+
+		// Modification times may be rounded down to seconds
+		let since = self
+			.started_at
+			.checked_sub(Duration::from_secs(1))
+			.unwrap_or(self.started_at);
+
+		let LaunchDirs { game, working, .. } = &self.dirs;
+
+		let mut dirs = vec![working.clone()];
+
+		if game != working {
+			dirs.push(game.clone());
+		}
+
+		let crash = CrashReport::find(game, &dirs, self.pid, since).await;
+
+		let tail = self
+			.tail
+			.lock()
+			.map(|it| it.iter().cloned().collect::<Vec<_>>())
+			.unwrap_or_default();
+
+		let mut causes = crash
+			.as_ref()
+			.map(|it| it.causes.clone())
+			.unwrap_or_default();
+
+		for cause in crash_causes(tail.iter().map(String::as_str)) {
+			if !causes.contains(&cause) {
+				causes.push(cause);
+			}
+		}
+
+		debug!(?kind, ?crash, ?causes, "Game crashed");
+
+		Ok(GameExit {
+			kind,
+			crash,
+			causes,
+		})
+	}
+
+	/// Asks the game to close, like closing its window does
+	pub fn terminate(&mut self) -> Result<(), Error> {
+		// The id is gone once the exit is collected, the pid may belong to
+		// another process by then
+		let Some(pid) = self.child.id() else {
+			return Ok(());
+		};
+
+		#[cfg(target_family = "unix")]
+		{
+			// SAFETY: sending a signal does not touch any memory of this process
+			if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+				return Err(std::io::Error::last_os_error().into());
+			}
+		}
+
+		#[cfg(target_family = "windows")]
+		{
+			let status = std::process::Command::new("taskkill")
+				.args(["/PID", &pid.to_string()])
+				.status()?;
+
+			if !status.success() {
+				return Err(std::io::Error::other(format!("taskkill exited with {status}")).into());
+			}
+		}
+
+		self.stopped = true;
+
+		Ok(())
+	}
+
+	/// Kills the game immediately and waits for it to exit
+	pub async fn kill(&mut self) -> Result<(), Error> {
+		self.child.kill().await?;
+		self.stopped = true;
+
+		Ok(())
+	}
+}
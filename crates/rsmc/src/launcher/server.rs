@@ -0,0 +1,358 @@
+use {
+	super::{
+		find_java,
+		forward_lines,
+		PROBE_EXECUTABLE_NAME,
+	},
+	crate::{
+		spec::*,
+		tracing::{
+			debug,
+			trace,
+		},
+		Error,
+	},
+	std::{
+		fmt::{
+			Display,
+			Formatter,
+		},
+		path::{
+			Path,
+			PathBuf,
+		},
+		process::Stdio,
+		sync::Arc,
+	},
+	tokio::{
+		fs,
+		io::AsyncWriteExt,
+		process::{
+			Child,
+			ChildStdin,
+			Command,
+		},
+		sync::{
+			mpsc,
+			mpsc::Receiver,
+		},
+	},
+};
+
+#[derive(Debug, Clone)]
+enum PropertyLine {
+	Entry {
+		key: String,
+		value: String,
+	},
+	/// Comments and blank lines, kept as is
+	Other(String),
+}
+
+/// Contents of `server.properties`, comments and order of entries are
+/// preserved when written back. Values are stored without unescaping.
+#[derive(Debug, Clone, Default)]
+pub struct ServerProperties {
+	lines: Vec<PropertyLine>,
+}
+
+impl ServerProperties {
+	pub fn parse(content: &str) -> Self {
+		let lines = content
+			.lines()
+			.map(|line| {
+				let trimmed = line.trim_start();
+
+				if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+					return PropertyLine::Other(line.to_owned());
+				}
+
+				match trimmed.split_once(['=', ':']) {
+					Some((key, value)) => PropertyLine::Entry {
+						key: key.trim().to_owned(),
+						value: value.trim_start().to_owned(),
+					},
+					None => PropertyLine::Entry {
+						key: trimmed.trim_end().to_owned(),
+						value: String::new(),
+					},
+				}
+			})
+			.collect();
+
+		Self { lines }
+	}
+
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.lines.iter().find_map(|line| match line {
+			PropertyLine::Entry { key: it, value } if it == key => Some(value.as_str()),
+			_ => None,
+		})
+	}
+
+	/// Replaces the value of an existing entry or appends a new one
+	pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+		let key = key.into();
+		let value = value.into();
+
+		for line in &mut self.lines {
+			if let PropertyLine::Entry {
+				key: it,
+				value: old,
+			} = line
+			{
+				if *it == key {
+					*old = value;
+					return;
+				}
+			}
+		}
+
+		self.lines.push(PropertyLine::Entry { key, value });
+	}
+}
+
+impl Display for ServerProperties {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		for line in &self.lines {
+			match line {
+				PropertyLine::Entry { key, value } => writeln!(f, "{key}={value}")?,
+				PropertyLine::Other(it) => writeln!(f, "{it}")?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Runs the dedicated server of a version in its own instance directory
+#[derive(Debug, Clone)]
+pub struct ServerLauncher {
+	pub root: PathBuf,
+	pub manifest: Box<NativeManifest>,
+	/// Working directory of the server with its worlds and configs
+	pub instance_dir: PathBuf,
+	pub extra_jvm_args: Vec<String>,
+	/// Overrides written to `server.properties` before the launch
+	pub properties: Vec<(String, String)>,
+	/// Writes `eula=true` to `eula.txt`, the server refuses to start otherwise
+	pub accept_eula: bool,
+	/// Java executable to use instead of the bundled runtime
+	pub java_path: Option<PathBuf>,
+}
+
+/// Stdin of a running server
+#[derive(Debug)]
+pub struct ServerConsole {
+	pub stdin: ChildStdin,
+}
+
+impl ServerConsole {
+	/// Sends a console command, e.g. `stop` or `say hello`
+	pub async fn send_command(&mut self, command: &str) -> Result<(), Error> {
+		trace!("Server command: {}", command);
+
+		self
+			.stdin
+			.write_all(format!("{}\n", command.trim_end()).as_bytes())
+			.await?;
+		self.stdin.flush().await?;
+
+		Ok(())
+	}
+}
+
+/// Running server with its console
+#[derive(Debug)]
+pub struct ServerProcess {
+	pub child: Child,
+	pub console: ServerConsole,
+	/// Lines of stdout and stderr
+	pub output: Receiver<String>,
+}
+
+impl ServerLauncher {
+	pub async fn try_from_root(root: &Path, id: &str, instance_dir: &Path) -> Result<Self, Error> {
+		let manifest = resolve_manifest(root, id).await?;
+
+		debug!(?id, "Manifest loaded");
+
+		Ok(Self {
+			root: root.to_owned(),
+			manifest,
+			instance_dir: instance_dir.to_owned(),
+			extra_jvm_args: Vec::new(),
+			properties: Vec::new(),
+			accept_eula: false,
+			java_path: None,
+		})
+	}
+
+	pub fn set_property(&mut self, key: impl Into<String>, value: impl Into<String>) {
+		self.properties.push((key.into(), value.into()));
+	}
+
+	pub async fn java_executable(&self) -> Result<PathBuf, Error> {
+		match &self.java_path {
+			Some(java_path) => Ok(java_path.clone()),
+			None => {
+				find_java(
+					&self.root,
+					&self.manifest.java_version,
+					PROBE_EXECUTABLE_NAME,
+				)
+				.await
+			}
+		}
+	}
+
+	async fn prepare_eula(&self) -> Result<(), Error> {
+		let path = self.instance_dir.join("eula.txt");
+
+		if self.accept_eula {
+			fs::write(&path, "eula=true\n").await?;
+
+			return Ok(());
+		}
+
+		let accepted = match fs::read_to_string(&path).await {
+			Ok(content) => ServerProperties::parse(&content)
+				.get("eula")
+				.is_some_and(|it| it.trim().eq_ignore_ascii_case("true")),
+			Err(_) => false,
+		};
+
+		if accepted {
+			Ok(())
+		} else {
+			Err(Error::EulaNotAccepted)
+		}
+	}
+
+	async fn prepare_properties(&self) -> Result<(), Error> {
+		if self.properties.is_empty() {
+			return Ok(());
+		}
+
+		let path = self.instance_dir.join("server.properties");
+
+		let mut properties = match fs::read_to_string(&path).await {
+			Ok(content) => ServerProperties::parse(&content),
+			Err(_) => ServerProperties::default(),
+		};
+
+		for (key, value) in &self.properties {
+			properties.set(key.as_str(), value.as_str());
+		}
+
+		fs::write(&path, properties.to_string()).await?;
+
+		Ok(())
+	}
+
+	pub async fn launch(self) -> Result<ServerProcess, Error> {
+		// Inherited versions use the server of the version they inherit from
+		let server_jar = server_jar_path(&self.root, self.manifest.jar_id());
+
+		if !fs::try_exists(&server_jar).await? {
+			return Err(Error::Inconsistent(format!(
+				"Server jar of {} is not installed",
+				self.manifest.id
+			)));
+		}
+
+		let executable = self.java_executable().await?;
+		trace!(?executable, ?server_jar);
+
+		fs::create_dir_all(&self.instance_dir).await?;
+
+		self.prepare_eula().await?;
+		self.prepare_properties().await?;
+
+		let mut command = Command::new(executable);
+
+		command.current_dir(&self.instance_dir);
+		command.stdin(Stdio::piped());
+		command.stdout(Stdio::piped());
+		command.stderr(Stdio::piped());
+
+		command.args(self.extra_jvm_args);
+		command.arg("-jar");
+		command.arg(server_jar);
+		command.arg("nogui");
+
+		trace!(?command);
+
+		let mut child = command.spawn()?;
+		trace!("Spawned");
+
+		let (tx, output) = mpsc::channel::<String>(64);
+		let tx = Arc::new(tx);
+
+		forward_lines(child.stdout.take().unwrap(), tx.clone(), String::from);
+		forward_lines(child.stderr.take().unwrap(), tx, String::from);
+
+		let console = ServerConsole {
+			stdin: child.stdin.take().unwrap(),
+		};
+
+		Ok(ServerProcess {
+			child,
+			console,
+			output,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PROPERTIES: &str = "\
+#Minecraft server properties
+#Mon Jan 01 12:00:00 UTC 2024
+
+! legacy comment
+motd=A Minecraft Server
+server-port: 25565
+white-list
+level-seed=
+";
+
+	#[test]
+	fn reads_properties() {
+		let properties = ServerProperties::parse(PROPERTIES);
+
+		assert_eq!(properties.get("motd"), Some("A Minecraft Server"));
+		assert_eq!(properties.get("server-port"), Some("25565"));
+		assert_eq!(properties.get("white-list"), Some(""));
+		assert_eq!(properties.get("level-seed"), Some(""));
+		assert_eq!(properties.get("#Minecraft server properties"), None);
+		assert_eq!(properties.get("online-mode"), None);
+	}
+
+	#[test]
+	fn round_trips_properties() {
+		let mut properties = ServerProperties::parse(PROPERTIES);
+
+		properties.set("server-port", "25566");
+		properties.set("online-mode", "false");
+
+		assert_eq!(properties.get("server-port"), Some("25566"));
+		assert_eq!(properties.get("online-mode"), Some("false"));
+		assert_eq!(
+			properties.to_string(),
+			"\
+#Minecraft server properties
+#Mon Jan 01 12:00:00 UTC 2024
+
+! legacy comment
+motd=A Minecraft Server
+server-port=25566
+white-list=
+level-seed=
+online-mode=false
+"
+		);
+	}
+}
@@ -0,0 +1,156 @@
+use {
+	super::Launcher,
+	crate::{
+		spec::*,
+		tracing::{
+			debug,
+			warn,
+		},
+		Error,
+	},
+	serde::{
+		Deserialize,
+		Serialize,
+	},
+	std::{
+		net::Ipv6Addr,
+		path::Path,
+	},
+};
+
+/// Place the game opens right after start instead of the title screen
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum LaunchTarget {
+	/// World by its directory name in `saves`
+	Singleplayer {
+		world: String,
+	},
+	Multiplayer {
+		host: String,
+		port: Option<u16>,
+	},
+	Realm {
+		id: String,
+	},
+}
+
+impl LaunchTarget {
+	/// Feature enabling the quick play argument and its placeholder
+	fn quick_play(&self) -> (&'static str, &'static str, String) {
+		match self {
+			LaunchTarget::Singleplayer { world } => (
+				features::QUICK_PLAY_SINGLEPLAYER,
+				"quickPlaySingleplayer",
+				world.clone(),
+			),
+			LaunchTarget::Multiplayer { host, port } => (
+				features::QUICK_PLAY_MULTIPLAYER,
+				"quickPlayMultiplayer",
+				match port {
+					// The port would be read as part of the address otherwise
+					Some(port) if host.parse::<Ipv6Addr>().is_ok() => format!("[{host}]:{port}"),
+					Some(port) => format!("{host}:{port}"),
+					None => host.clone(),
+				},
+			),
+			LaunchTarget::Realm { id } => (features::QUICK_PLAY_REALMS, "quickPlayRealms", id.clone()),
+		}
+	}
+}
+
+impl Launcher {
+	/// Whether any game argument of the manifest uses the placeholder,
+	/// regardless of its rules
+	fn uses_placeholder(&self, name: &str) -> bool {
+		let Args { game, .. } = self.manifest.arguments.clone().into_modern().arguments;
+
+		game
+			.iter()
+			.flat_map(|arg| match arg {
+				Argument::Constant(it) => vec![it.as_str()],
+				Argument::Conditional { value, .. } => match value {
+					ConditionalArgument::Single(it) => vec![it.as_str()],
+					ConditionalArgument::List(it) => it.iter().map(String::as_str).collect(),
+				},
+			})
+			.any(|it| placeholders(it).contains(&name))
+	}
+
+	/// Opens the target on start with quick play, or with the `--server` and
+	/// `--port` arguments on versions that predate it
+	pub fn set_target(&mut self, target: &LaunchTarget) -> Result<(), Error> {
+		let (feature, placeholder, value) = target.quick_play();
+
+		if self.uses_placeholder(placeholder) {
+			debug!(?target, "Using quick play");
+
+			self.set_feature(feature, true);
+			self.vars.insert(placeholder.into(), value);
+
+			return Ok(());
+		}
+
+		match target {
+			LaunchTarget::Multiplayer { host, port } => {
+				debug!(?target, "Using legacy server arguments");
+
+				self
+					.extra_game_args
+					.extend(["--server".into(), host.clone()]);
+
+				if let Some(port) = port {
+					self
+						.extra_game_args
+						.extend(["--port".into(), port.to_string()]);
+				}
+
+				Ok(())
+			}
+			_ => Err(Error::InvalidLauncherConfig(format!(
+				"Version {} can not open {target:?} on start",
+				self.manifest.id
+			))),
+		}
+	}
+
+	/// File the game logs quick play sessions to, relative to the working
+	/// directory, ignored by versions without quick play
+	pub fn set_quick_play_path(&mut self, path: &Path) -> Result<(), Error> {
+		if !self.uses_placeholder("quickPlayPath") {
+			warn!("Version {} does not support quick play", self.manifest.id);
+
+			return Ok(());
+		}
+
+		let path = self.working_directory().join(path);
+		let path = path.to_str().ok_or(Error::InvalidUtf8Path)?;
+
+		self.set_feature(features::QUICK_PLAYS_SUPPORT, true);
+		self.vars.insert("quickPlayPath".into(), path.into());
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn address(host: &str, port: Option<u16>) -> String {
+		LaunchTarget::Multiplayer {
+			host: host.into(),
+			port,
+		}
+		.quick_play()
+		.2
+	}
+
+	#[test]
+	fn brackets_ipv6_hosts() {
+		assert_eq!(address("example.com", Some(25565)), "example.com:25565");
+		assert_eq!(address("127.0.0.1", Some(25565)), "127.0.0.1:25565");
+		assert_eq!(address("::1", Some(25565)), "[::1]:25565");
+		assert_eq!(address("::1", None), "::1");
+	}
+}
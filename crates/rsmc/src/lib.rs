@@ -3,6 +3,8 @@
 pub(crate) mod private;
 
 pub mod error;
+#[cfg(feature = "install")]
+pub mod install;
 #[cfg(feature = "launcher")]
 pub mod launcher;
 #[cfg(feature = "spec")]
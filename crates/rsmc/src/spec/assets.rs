@@ -0,0 +1,30 @@
+use {
+	super::*,
+	std::path::Path,
+	tokio::fs,
+};
+
+pub fn asset_index_path(root: &Path, id: &str) -> PathBuf {
+	root
+		.join("assets")
+		.join("indexes")
+		.join(format!("{id}.json"))
+}
+
+pub fn log_config_path(root: &Path, file: &LoggingFile) -> PathBuf {
+	root.join("assets").join("log_configs").join(&file.id)
+}
+
+/// Dedicated server jar of the version, kept next to the client jar
+pub fn server_jar_path(root: &Path, id: &str) -> PathBuf {
+	root
+		.join("versions")
+		.join(id)
+		.join(format!("{id}-server.jar"))
+}
+
+pub async fn read_asset_index(root: &Path, id: &str) -> Result<AssetIndex, Error> {
+	let content = fs::read_to_string(asset_index_path(root, id)).await?;
+
+	Ok(serde_json::from_str(&content)?)
+}
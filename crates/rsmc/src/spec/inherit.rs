@@ -0,0 +1,273 @@
+use {
+	super::*,
+	crate::tracing::{
+		debug,
+		trace,
+	},
+	std::path::Path,
+	tokio::fs,
+};
+
+impl Library {
+	pub fn name(&self) -> &ArtifactName {
+		match self {
+			Library::Native(it) => &it.name,
+			Library::Seminative(it) => &it.name,
+			Library::Common(it) => &it.name,
+			Library::Custom(it) => &it.name,
+		}
+	}
+}
+
+impl ArtifactName {
+	/// Whether both names point to the same artifact regardless of its version
+	pub fn is_same_artifact(&self, other: &ArtifactName) -> bool {
+		self.package == other.package && self.name == other.name && self.classifier == other.classifier
+	}
+}
+
+impl InheritedManifest {
+	/// Merges this manifest into its parent.
+	///
+	/// Libraries of the inheriting manifest take precedence over the parent's
+	/// ones with the same artifact, everything that is not declared by the
+	/// inheriting manifest is taken from the parent.
+	pub fn merge(self, parent: NativeManifest) -> NativeManifest {
+		let NativeManifest {
+			id: parent_id,
+			assets,
+			asset_index,
+			downloads,
+			java_version,
+			arguments,
+			libraries: parent_libraries,
+			logging,
+			jar: parent_jar,
+			mut extra,
+			..
+		} = parent;
+
+		let mut libraries = self.libraries;
+
+		let inherited: Vec<_> = parent_libraries
+			.into_iter()
+			.filter(|parent_lib| {
+				let overridden = libraries
+					.iter()
+					.any(|lib| lib.name().is_same_artifact(parent_lib.name()));
+
+				if overridden {
+					trace!("Overridden by {}: {}", self.id, parent_lib.name());
+				}
+
+				!overridden
+			})
+			.collect();
+
+		libraries.extend(inherited);
+		extra.extend(self.extra);
+
+		let arguments = match self.arguments {
+			Some(it) => arguments.merge(it),
+			None => arguments,
+		};
+
+		NativeManifest {
+			id: self.id,
+			assets,
+			asset_index,
+			downloads,
+			main_class: self.main_class,
+			java_version,
+			arguments,
+			libraries,
+			logging,
+			version_type: self.version_type,
+			jar: self.jar.or(parent_jar).or(Some(parent_id)),
+			extra,
+		}
+	}
+}
+
+pub fn manifest_path(root: &Path, id: &str) -> PathBuf {
+	root.join("versions").join(id).join(format!("{id}.json"))
+}
+
+pub async fn read_manifest(root: &Path, id: &str) -> Result<Manifest, Error> {
+	let manifest_path = manifest_path(root, id);
+
+	debug!("Reading manifest from {:?}", manifest_path);
+
+	let content = fs::read_to_string(manifest_path).await?;
+
+	Ok(serde_json::from_str(&content)?)
+}
+
+/// Validates the manifest with given id in the root, including the entries
+/// that keep it from being read, see [`Manifest::validate_json`]
+pub async fn validate_manifest(root: &Path, id: &str) -> Result<Vec<Diagnostic>, Error> {
+	let content = fs::read_to_string(manifest_path(root, id)).await?;
+
+	Ok(Manifest::validate_json(&content))
+}
+
+/// Reads the manifest with given id from the root and merges it with every
+/// manifest it inherits from.
+pub async fn resolve_manifest(root: &Path, id: &str) -> Result<Box<NativeManifest>, Error> {
+	let mut chain: Vec<Box<InheritedManifest>> = Vec::new();
+	let mut visited: Vec<String> = Vec::new();
+	let mut next = id.to_owned();
+
+	let base = loop {
+		if visited.contains(&next) {
+			visited.push(next);
+
+			return Err(Error::InheritanceCycle(visited.join(" -> ")));
+		}
+
+		let manifest = read_manifest(root, &next).await?;
+		visited.push(next);
+
+		match manifest {
+			Manifest::Native(it) => break it,
+			Manifest::Inherited(it) => {
+				trace!("{} inherits from {}", it.id, it.inherits_from);
+
+				next = it.inherits_from.clone();
+				chain.push(it);
+			}
+		}
+	};
+
+	debug!(?id, depth = chain.len(), "Resolved inheritance chain");
+
+	Ok(Box::new(
+		chain
+			.into_iter()
+			.rev()
+			.fold(*base, |parent, child| child.merge(parent)),
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PARENT: &str = r#"{
+		"id": "1.20",
+		"assets": "5",
+		"assetIndex": {
+			"id": "5",
+			"sha1": "0000000000000000000000000000000000000000",
+			"size": 1,
+			"totalSize": 1,
+			"url": "https://piston-meta.mojang.com/v1/packages/5.json"
+		},
+		"downloads": {
+			"client": {
+				"sha1": "0000000000000000000000000000000000000000",
+				"size": 1,
+				"url": "https://piston-data.mojang.com/v1/objects/client.jar"
+			}
+		},
+		"mainClass": "net.minecraft.client.main.Main",
+		"javaVersion": { "component": "java-runtime-gamma", "majorVersion": 17 },
+		"arguments": { "game": ["--username"], "jvm": ["-cp"] },
+		"libraries": [
+			{ "name": "org.ow2.asm:asm:9.3", "url": "https://libraries.minecraft.net/" },
+			{ "name": "com.google.guava:guava:31.1-jre", "url": "https://libraries.minecraft.net/" }
+		],
+		"type": "release"
+	}"#;
+
+	const CHILD: &str = r#"{
+		"id": "fabric",
+		"inheritsFrom": "1.20",
+		"mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+		"arguments": { "game": ["--fabric"], "jvm": ["-Dfabric=1"] },
+		"libraries": [
+			{ "name": "org.ow2.asm:asm:9.6", "url": "https://maven.fabricmc.net/" }
+		],
+		"type": "release"
+	}"#;
+
+	fn merged() -> NativeManifest {
+		let parent: NativeManifest = serde_json::from_str(PARENT).unwrap();
+		let child: InheritedManifest = serde_json::from_str(CHILD).unwrap();
+
+		child.merge(parent)
+	}
+
+	fn constants(args: &[Argument]) -> Vec<&str> {
+		args
+			.iter()
+			.filter_map(|it| match it {
+				Argument::Constant(it) => Some(it.as_str()),
+				_ => None,
+			})
+			.collect()
+	}
+
+	#[test]
+	fn takes_identity_from_child() {
+		let manifest = merged();
+
+		assert_eq!(manifest.id, "fabric");
+		assert_eq!(
+			manifest.main_class,
+			"net.fabricmc.loader.impl.launch.knot.KnotClient"
+		);
+		assert_eq!(manifest.jar_id(), "1.20");
+		assert_eq!(manifest.java_version.major_version, 17);
+	}
+
+	#[test]
+	fn overrides_parent_libraries() {
+		let names: Vec<_> = merged()
+			.libraries
+			.iter()
+			.map(|it| it.name().to_string())
+			.collect();
+
+		assert_eq!(
+			names,
+			["org.ow2.asm:asm:9.6", "com.google.guava:guava:31.1-jre"]
+		);
+	}
+
+	#[test]
+	fn appends_child_arguments() {
+		let ModernArgs { arguments } = merged().arguments.into();
+
+		assert_eq!(constants(&arguments.game), ["--username", "--fabric"]);
+		assert_eq!(constants(&arguments.jvm), ["-cp", "-Dfabric=1"]);
+	}
+
+	#[test]
+	fn keeps_explicit_jar() {
+		let parent: NativeManifest = serde_json::from_str(PARENT).unwrap();
+		let child: InheritedManifest =
+			serde_json::from_str(&CHILD.replacen('{', r#"{ "jar": "1.20-custom","#, 1)).unwrap();
+
+		assert_eq!(child.merge(parent).jar_id(), "1.20-custom");
+	}
+
+	#[test]
+	fn rejects_malformed_arguments() {
+		let child = CHILD.replace(r#"["--fabric"]"#, r#""--fabric""#);
+
+		assert!(serde_json::from_str::<InheritedManifest>(&child).is_err());
+		assert!(serde_json::from_str::<Manifest>(&child).is_err());
+
+		let without_arguments = r#"{
+			"id": "fabric",
+			"inheritsFrom": "1.20",
+			"mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+			"type": "release"
+		}"#;
+
+		let child: InheritedManifest = serde_json::from_str(without_arguments).unwrap();
+
+		assert!(child.arguments.is_none());
+	}
+}
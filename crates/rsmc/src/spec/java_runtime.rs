@@ -0,0 +1,77 @@
+use {
+	super::*,
+	std::collections::BTreeMap,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaRuntimeAvailability {
+	pub group: u32,
+	pub progress: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaRuntimeVersion {
+	pub name: String,
+	pub released: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaRuntime {
+	pub availability: JavaRuntimeAvailability,
+	pub manifest: Resource,
+	pub version: JavaRuntimeVersion,
+}
+
+/// Mojang java-runtime `all.json`, runtimes by platform and component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaRuntimeIndex {
+	#[serde(flatten)]
+	pub platforms: HashMap<String, HashMap<String, Vec<JavaRuntime>>>,
+}
+
+impl JavaRuntimeIndex {
+	/// Platform key used by the index for the given os and arch
+	pub fn platform(os: &Os, arch: &Arch) -> Result<&'static str, Error> {
+		match (os, arch) {
+			(Os::Linux, Arch::X64) => Ok("linux"),
+			(Os::Linux, Arch::X86) => Ok("linux-i386"),
+			(Os::Osx, Arch::X64) => Ok("mac-os"),
+			(Os::Osx, Arch::Arm64) => Ok("mac-os-arm64"),
+			(Os::Windows, Arch::X64) => Ok("windows-x64"),
+			(Os::Windows, Arch::X86) => Ok("windows-x86"),
+			(Os::Windows, Arch::Arm64) => Ok("windows-arm64"),
+			_ => Err(Error::UnsupportedPlatform),
+		}
+	}
+
+	pub fn find(&self, platform: &str, component: &str) -> Option<&JavaRuntime> {
+		self.platforms.get(platform)?.get(component)?.first()
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaRuntimeFileDownloads {
+	pub raw: Resource,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub lzma: Option<Resource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum JavaRuntimeFile {
+	File {
+		#[serde(default)]
+		executable: bool,
+		downloads: Box<JavaRuntimeFileDownloads>,
+	},
+	Directory,
+	Link {
+		target: String,
+	},
+}
+
+/// Files of a single java runtime component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaRuntimeManifest {
+	pub files: BTreeMap<String, JavaRuntimeFile>,
+}
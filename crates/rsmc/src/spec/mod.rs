@@ -1,60 +1,163 @@
 use {
-	crate::error::Error,
+	crate::{
+		error::Error,
+		tracing::warn,
+	},
+	regex::Regex,
 	serde::{
-		de::Visitor,
+		de::{
+			Error as _,
+			Unexpected,
+			Visitor,
+		},
 		Deserialize,
 		Deserializer,
 		Serialize,
 		Serializer,
 	},
+	serde_json::{
+		Map,
+		Value,
+	},
 	std::{
 		collections::HashMap,
-		ffi::OsStr,
 		fmt::{
 			Display,
 			Formatter,
 		},
-		path::{
-			Component,
-			PathBuf,
+		path::PathBuf,
+		str::FromStr,
+		sync::{
+			Mutex,
+			OnceLock,
 		},
 	},
+	url::Url,
+};
+
+mod assets;
+mod inherit;
+mod java_runtime;
+mod validate;
+mod version_index;
+
+pub use {
+	assets::*,
+	inherit::*,
+	java_runtime::*,
+	validate::*,
+	version_index::*,
 };
 
+/// Fields of a manifest entry that are not modelled by its type, kept so the
+/// entry is written back unchanged
+pub type Extra = Map<String, Value>;
+
+/// Whether the value is a single path component that stays in the directory it
+/// is joined to
+pub(crate) fn is_path_segment(value: &str) -> bool {
+	!value.is_empty() && value != "." && value != ".." && !value.contains(['/', '\\', ':'])
+}
+
+/// Relative path from a `/`-separated name of a remote index, rejecting
+/// absolute names and names leaving the directory it is joined to
+pub fn safe_relative_path(name: &str) -> Result<PathBuf, Error> {
+	if !name.split('/').all(is_path_segment) {
+		return Err(Error::UnsafePath(name.to_owned()));
+	}
+
+	Ok(name.split('/').collect())
+}
+
+/// Maven coordinates in `group:artifact:version[:classifier][@extension]`
+/// format
 #[derive(Debug, Clone)]
 pub struct ArtifactName {
 	pub package: PathBuf,
 	pub name: String,
 	pub version: String,
+	pub classifier: Option<String>,
+	pub extension: Option<String>,
 }
 
 impl ArtifactName {
+	pub const DEFAULT_EXTENSION: &'static str = "jar";
+
+	/// Directory of the artifact relative to the repository root
 	pub fn to_path(&self) -> PathBuf {
 		self.package.join(&self.name).join(&self.version)
 	}
+
+	/// `artifact-version[-classifier].extension`
+	pub fn file_name(&self) -> String {
+		let extension = self.extension.as_deref().unwrap_or(Self::DEFAULT_EXTENSION);
+
+		match &self.classifier {
+			Some(classifier) => format!(
+				"{}-{}-{}.{}",
+				self.name, self.version, classifier, extension
+			),
+			None => format!("{}-{}.{}", self.name, self.version, extension),
+		}
+	}
+
+	/// Path of the artifact file relative to the repository root
+	pub fn to_file_path(&self) -> PathBuf {
+		self.to_path().join(self.file_name())
+	}
+}
+
+impl FromStr for ArtifactName {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || Error::InvalidArtifactName(s.to_owned());
+
+		let (coords, extension) = match s.split_once('@') {
+			Some((coords, extension)) => (coords, Some(extension)),
+			None => (s, None),
+		};
+
+		let parts: Vec<&str> = coords.split(':').collect();
+
+		// Every part ends up in a path, so none of them may leave its directory
+		if !(3..=4).contains(&parts.len())
+			|| !parts.iter().all(|it| is_path_segment(it))
+			|| !parts[0].split('.').all(is_path_segment)
+			|| extension.is_some_and(|it| !is_path_segment(it))
+		{
+			return Err(invalid());
+		}
+
+		Ok(ArtifactName {
+			package: parts[0].split('.').collect(),
+			name: parts[1].into(),
+			version: parts[2].into(),
+			classifier: parts.get(3).map(|&it| it.into()),
+			extension: extension.map(Into::into),
+		})
+	}
 }
 
 impl Display for ArtifactName {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		let components: Vec<&OsStr> = self
+		let package: Vec<_> = self
 			.package
 			.components()
-			.map(|it| match it {
-				Component::Normal(name) => Ok(name),
-				_ => unreachable!("Invalid path component"),
-			})
-			.collect::<Result<_, std::fmt::Error>>()?;
-
-		write!(
-			f,
-			"{}:{}:{}",
-			components
-				.join(OsStr::new("."))
-				.to_str()
-				.expect("Invalid os string"),
-			self.name,
-			self.version
-		)
+			.map(|it| it.as_os_str().to_string_lossy())
+			.collect();
+
+		write!(f, "{}:{}:{}", package.join("."), self.name, self.version)?;
+
+		if let Some(classifier) = &self.classifier {
+			write!(f, ":{classifier}")?;
+		}
+
+		if let Some(extension) = &self.extension {
+			write!(f, "@{extension}")?;
+		}
+
+		Ok(())
 	}
 }
 
@@ -71,7 +174,7 @@ impl<'de> Deserialize<'de> for ArtifactName {
 			fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
 				write!(
 					formatter,
-					"a string in artifact format `org.name.pkg:artifact:version`"
+					"a string in artifact format `org.name.pkg:artifact:version[:classifier][@extension]`"
 				)
 			}
 
@@ -79,13 +182,8 @@ impl<'de> Deserialize<'de> for ArtifactName {
 			where
 				E: serde::de::Error,
 			{
-				let parts: Vec<&str> = v.split(':').collect();
-
-				Ok(ArtifactName {
-					package: parts[0].split('.').collect(),
-					name: parts[1].into(),
-					version: parts[2].into(),
-				})
+				v.parse()
+					.map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
 			}
 
 			fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
@@ -136,16 +234,61 @@ impl Os {
 pub enum Arch {
 	X64,
 	X86,
+	Arm64,
 }
 
 impl Arch {
 	pub fn target() -> Self {
-		if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+		if cfg!(target_arch = "x86_64") {
 			Self::X64
+		} else if cfg!(target_arch = "aarch64") {
+			Self::Arm64
 		} else {
 			Self::X86
 		}
 	}
+
+	/// Value of the `${arch}` placeholder in native classifiers
+	pub fn bits(&self) -> &'static str {
+		match self {
+			Self::X64 | Self::Arm64 => "64",
+			Self::X86 => "32",
+		}
+	}
+}
+
+/// Version of the running OS in the same format Java reports as `os.version`,
+/// detected once per process
+pub fn os_version() -> Option<String> {
+	static OS_VERSION: OnceLock<Option<String>> = OnceLock::new();
+
+	OS_VERSION.get_or_init(detect_os_version).clone()
+}
+
+fn detect_os_version() -> Option<String> {
+	use std::process::Command;
+
+	let output = |program: &str, args: &[&str]| -> Option<String> {
+		let output = Command::new(program).args(args).output().ok()?;
+
+		String::from_utf8(output.stdout).ok()
+	};
+
+	if cfg!(target_os = "linux") {
+		std::fs::read_to_string("/proc/sys/kernel/osrelease")
+			.ok()
+			.map(|it| it.trim().to_owned())
+	} else if cfg!(target_os = "macos") {
+		output("sw_vers", &["-productVersion"]).map(|it| it.trim().to_owned())
+	} else if cfg!(target_os = "windows") {
+		// Microsoft Windows [Version 10.0.19045.3693]
+		let ver = output("cmd", &["/C", "ver"])?;
+		let (_, version) = ver.trim().trim_end_matches(']').rsplit_once(' ')?;
+
+		Some(version.splitn(3, '.').take(2).collect::<Vec<_>>().join("."))
+	} else {
+		None
+	}
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -159,13 +302,78 @@ pub enum RuleAction {
 #[serde(rename_all = "lowercase")]
 pub enum RuleCondition {
 	Os {
+		#[serde(default, skip_serializing_if = "Option::is_none")]
 		name: Option<Os>,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
 		arch: Option<Arch>,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
 		version: Option<String>,
 	},
 	Features(HashMap<String, bool>),
 }
 
+/// Well-known feature names used by Mojang manifests in argument rules
+pub mod features {
+	pub const DEMO_USER: &str = "is_demo_user";
+	pub const CUSTOM_RESOLUTION: &str = "has_custom_resolution";
+	pub const QUICK_PLAYS_SUPPORT: &str = "has_quick_plays_support";
+	pub const QUICK_PLAY_SINGLEPLAYER: &str = "is_quick_play_singleplayer";
+	pub const QUICK_PLAY_MULTIPLAYER: &str = "is_quick_play_multiplayer";
+	pub const QUICK_PLAY_REALMS: &str = "is_quick_play_realms";
+}
+
+/// Environment the rules are evaluated against
+#[derive(Debug, Clone)]
+pub struct RuleContext {
+	pub os: Os,
+	pub arch: Arch,
+	pub os_version: Option<String>,
+	pub features: HashMap<String, bool>,
+}
+
+impl RuleContext {
+	/// Context of the current platform with no features enabled
+	pub fn target() -> Result<Self, Error> {
+		Ok(Self {
+			os: Os::target()?,
+			arch: Arch::target(),
+			os_version: os_version(),
+			features: HashMap::new(),
+		})
+	}
+
+	pub fn with_features(mut self, features: HashMap<String, bool>) -> Self {
+		self.features.extend(features);
+		self
+	}
+
+	pub fn is_enabled(&self, feature: &str) -> bool {
+		self.features.get(feature).copied().unwrap_or(false)
+	}
+
+	pub fn matches_os_version(&self, pattern: &str) -> bool {
+		let Some(os_version) = &self.os_version else {
+			return false;
+		};
+
+		// Manifests repeat a handful of patterns across all libraries
+		static PATTERNS: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+
+		let mut patterns = PATTERNS
+			.get_or_init(Default::default)
+			.lock()
+			.unwrap_or_else(|it| it.into_inner());
+
+		let regex = patterns.entry(pattern.to_owned()).or_insert_with(|| {
+			Regex::new(pattern)
+				.map_err(|err| warn!(?err, "Invalid os version pattern: {}", pattern))
+				.ok()
+		});
+
+		regex.as_ref().is_some_and(|it| it.is_match(os_version))
+	}
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rule {
 	pub action: RuleAction,
@@ -174,40 +382,36 @@ pub struct Rule {
 }
 
 impl Rule {
-	pub fn unpack_all(rules: &[Rule]) -> bool {
-		rules.iter().all(Rule::unpack)
+	pub fn unpack_all(rules: &[Rule], ctx: &RuleContext) -> bool {
+		rules.iter().all(|it| it.unpack(ctx))
 	}
 
-	pub fn unpack(&self) -> bool {
+	pub fn unpack(&self, ctx: &RuleContext) -> bool {
 		let mut allow = true;
 
 		match &self.condition {
-			Some(RuleCondition::Os { name, arch, .. }) => {
+			Some(RuleCondition::Os {
+				name,
+				arch,
+				version,
+			}) => {
 				if let Some(os_name) = &name {
-					allow = match os_name {
-						#[cfg(target_os = "linux")]
-						Os::Linux => true,
-						#[cfg(target_os = "windows")]
-						Os::Windows => true,
-						#[cfg(target_os = "macos")]
-						Os::Osx => true,
-						#[allow(unreachable_patterns)]
-						_ => false,
-					};
+					allow = *os_name == ctx.os;
 				}
 
 				if let Some(os_arch) = &arch {
-					allow = match os_arch {
-						#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
-						Arch::X64 => true,
-						#[cfg(target_arch = "x86")]
-						Arch::X86 => true,
-						#[allow(unreachable_patterns)]
-						_ => false,
-					};
+					allow = allow && *os_arch == ctx.arch;
 				}
+
+				if let Some(pattern) = &version {
+					allow = allow && ctx.matches_os_version(pattern);
+				}
+			}
+			Some(RuleCondition::Features(features)) => {
+				allow = features
+					.iter()
+					.all(|(name, expected)| ctx.is_enabled(name) == *expected);
 			}
-			Some(RuleCondition::Features(_)) => allow = false,
 			_ => {}
 		};
 
@@ -223,23 +427,38 @@ pub struct Artifact {
 	pub path: PathBuf,
 	pub sha1: String,
 	pub size: u64,
+	/// Empty for artifacts that are generated locally, e.g. by Forge installer
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub url: Option<String>,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Resource {
 	pub sha1: String,
 	pub size: u64,
+	pub url: Url,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommonLibraryArtifacts {
 	pub artifact: Artifact,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NativeLibraryArtifacts {
-	pub artifact: Artifact,
+	/// Missing for libraries that only consist of natives, e.g. LWJGL 2
+	/// platform jars
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub artifact: Option<Artifact>,
 	pub classifiers: HashMap<String, Artifact>,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 impl NativeLibraryArtifacts {
@@ -255,6 +474,8 @@ impl NativeLibraryArtifacts {
 pub struct CommonLibrary {
 	pub name: ArtifactName,
 	pub downloads: CommonLibraryArtifacts,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -262,7 +483,28 @@ pub struct NativeLibrary {
 	pub name: ArtifactName,
 	pub downloads: NativeLibraryArtifacts,
 	pub natives: Natives,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
 	pub rules: Vec<Rule>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub extract: Option<ExtractRules>,
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+/// Entries of the native jar that are not unpacked into the natives directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractRules {
+	/// Path prefixes, e.g. `META-INF/`
+	#[serde(default)]
+	pub exclude: Vec<String>,
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+impl ExtractRules {
+	pub fn is_excluded(&self, entry: &str) -> bool {
+		self.exclude.iter().any(|it| entry.starts_with(it.as_str()))
+	}
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -272,13 +514,13 @@ pub struct Natives {
 }
 
 impl Natives {
-	pub fn get_classifier_name(&self) -> Result<&str, Error> {
+	pub fn get_classifier_name(&self, ctx: &RuleContext) -> Result<String, Error> {
 		Ok(
 			self
 				.inner
-				.get(&Os::target()?)
+				.get(&ctx.os)
 				.ok_or(Error::InvalidManifest("Missing native classifier".into()))?
-				.as_str(),
+				.replace("${arch}", ctx.arch.bits()),
 		)
 	}
 }
@@ -288,11 +530,22 @@ pub struct SeminativeLibrary {
 	pub name: ArtifactName,
 	pub downloads: CommonLibraryArtifacts,
 	pub rules: Vec<Rule>,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CustomLibrary {
 	pub name: ArtifactName,
+	/// Base url of the maven repository hosting this library
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub url: Option<Url>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sha1: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub size: Option<u64>,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -315,25 +568,41 @@ impl Library {
 	}
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggingFile {
+	pub id: String,
+	pub sha1: String,
+	pub size: u64,
+	pub url: Url,
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ClientLogging {
 	pub argument: String,
+	pub file: LoggingFile,
 	#[serde(rename = "type")]
 	pub log_type: String,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Logging {
 	pub client: ClientLogging,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct JavaVersion {
 	pub component: String,
-	pub major_version: i32,
+	pub major_version: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -343,6 +612,9 @@ pub struct AssetIndexResource {
 	pub sha1: String,
 	pub size: u64,
 	pub total_size: u64,
+	pub url: Url,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -366,11 +638,22 @@ pub enum Argument {
 #[serde(rename_all = "snake_case")]
 pub struct PackageDownloads {
 	pub client: Resource,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub client_mappings: Option<Resource>,
+	/// Dedicated server jar, missing for versions older than 1.2.5
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub server: Option<Resource>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub server_mappings: Option<Resource>,
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Args {
+	#[serde(default)]
 	pub game: Vec<Argument>,
+	#[serde(default)]
 	pub jvm: Vec<Argument>,
 }
 
@@ -422,16 +705,22 @@ impl From<ArgsContainer> for ModernArgs {
 }
 
 impl ArgsContainer {
-	fn merge(self, with: ArgsContainer) -> Self {
+	/// Merges arguments of an inheriting manifest into the parent ones.
+	///
+	/// Modern arguments are appended to the parent's, while legacy
+	/// `minecraftArguments` are a complete command line and replace the
+	/// parent's game arguments.
+	pub fn merge(self, with: ArgsContainer) -> Self {
 		use ArgsContainer::*;
 
-		match with {
-			Modern(ModernArgs { arguments }) => {
+		match (self, with) {
+			(Legacy(_), Legacy(it)) => Legacy(it),
+			(parent, Modern(ModernArgs { arguments })) => {
 				let Args {
 					jvm: jvm_ext,
 					game: game_ext,
 				} = arguments;
-				let mut modern = self.into_modern();
+				let mut modern = parent.into_modern();
 				let Args {
 					ref mut jvm,
 					ref mut game,
@@ -442,11 +731,20 @@ impl ArgsContainer {
 
 				Modern(modern)
 			}
-			Legacy(args) => self.merge(Modern(args.into())),
+			(Modern(parent), Legacy(args)) => {
+				let ModernArgs { arguments } = args.into();
+
+				Modern(ModernArgs {
+					arguments: Args {
+						game: arguments.game,
+						jvm: parent.arguments.jvm,
+					},
+				})
+			}
 		}
 	}
 
-	fn into_modern(self) -> ModernArgs {
+	pub fn into_modern(self) -> ModernArgs {
 		use ArgsContainer::*;
 
 		match self {
@@ -468,9 +766,21 @@ pub struct NativeManifest {
 	#[serde(flatten)]
 	pub arguments: ArgsContainer,
 	pub libraries: Vec<Library>,
-	pub logging: Logging,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub logging: Option<Logging>,
 	#[serde(rename = "type")]
 	pub version_type: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub jar: Option<String>,
+	#[serde(flatten, deserialize_with = "deserialize_manifest_extra")]
+	pub extra: Extra,
+}
+
+impl NativeManifest {
+	/// Id of the version whose jar should be used as the game client
+	pub fn jar_id(&self) -> &str {
+		self.jar.as_deref().unwrap_or(&self.id)
+	}
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -479,11 +789,54 @@ pub struct InheritedManifest {
 	pub id: String,
 	pub inherits_from: String,
 	pub main_class: String,
-	#[serde(flatten)]
-	pub arguments: ArgsContainer,
+	#[serde(flatten, deserialize_with = "deserialize_inherited_arguments")]
+	pub arguments: Option<ArgsContainer>,
+	#[serde(default)]
 	pub libraries: Vec<Library>,
 	#[serde(rename = "type")]
 	pub version_type: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub jar: Option<String>,
+	#[serde(flatten, deserialize_with = "deserialize_manifest_extra")]
+	pub extra: Extra,
+}
+
+/// Arguments an inheriting manifest adds, if any. A flattened `Option` turns
+/// malformed arguments into `None`, which would drop them silently.
+fn deserialize_inherited_arguments<'de, D>(
+	deserializer: D,
+) -> Result<Option<ArgsContainer>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	let mut fields = Extra::deserialize(deserializer)?;
+
+	let arguments: Extra = ["arguments", "minecraftArguments"]
+		.into_iter()
+		.filter_map(|key| Some((key.to_owned(), fields.remove(key)?)))
+		.collect();
+
+	if arguments.is_empty() {
+		return Ok(None);
+	}
+
+	ArgsContainer::deserialize(Value::Object(arguments))
+		.map(Some)
+		.map_err(D::Error::custom)
+}
+
+/// Flattened arguments are seen by every flattened field, keep them out of
+/// the extra fields to not write them twice
+fn deserialize_manifest_extra<'de, D>(deserializer: D) -> Result<Extra, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	let mut extra = Extra::deserialize(deserializer)?;
+
+	extra.remove("arguments");
+	extra.remove("minecraftArguments");
+
+	Ok(extra)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -511,7 +864,104 @@ pub struct AssetObject {
 	pub size: u64,
 }
 
+impl AssetObject {
+	/// `xx/hash` path relative to the objects directory and resources server
+	pub fn object_path(&self) -> String {
+		format!("{}/{}", self.hash.get(..2).unwrap_or_default(), self.hash)
+	}
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AssetIndex {
 	pub objects: HashMap<String, AssetObject>,
+	/// Objects are expected in `assets/virtual/<index>` by their names
+	/// (`legacy` index)
+	#[serde(default, rename = "virtual", skip_serializing_if = "is_false")]
+	pub is_virtual: bool,
+	/// Objects are expected in `<game_directory>/resources` by their names
+	/// (`pre-1.6` index)
+	#[serde(default, skip_serializing_if = "is_false")]
+	pub map_to_resources: bool,
+}
+
+fn is_false(it: &bool) -> bool {
+	!*it
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_artifact_name() {
+		let name: ArtifactName = "org.lwjgl:lwjgl:3.3.1:natives-linux@zip".parse().unwrap();
+
+		assert_eq!(name.package, PathBuf::from("org/lwjgl"));
+		assert_eq!(name.name, "lwjgl");
+		assert_eq!(name.version, "3.3.1");
+		assert_eq!(name.classifier.as_deref(), Some("natives-linux"));
+		assert_eq!(name.extension.as_deref(), Some("zip"));
+		assert_eq!(
+			name.to_file_path(),
+			PathBuf::from("org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.zip")
+		);
+		assert_eq!(name.to_string(), "org.lwjgl:lwjgl:3.3.1:natives-linux@zip");
+	}
+
+	#[test]
+	fn parses_artifact_name_without_classifier() {
+		let name: ArtifactName = "com.mojang:brigadier:1.0.18".parse().unwrap();
+
+		assert_eq!(name.classifier, None);
+		assert_eq!(
+			name.to_file_path(),
+			PathBuf::from("com/mojang/brigadier/1.0.18/brigadier-1.0.18.jar")
+		);
+		assert_eq!(name.to_string(), "com.mojang:brigadier:1.0.18");
+	}
+
+	#[test]
+	fn rejects_invalid_artifact_names() {
+		for name in [
+			"",
+			"a:b",
+			"a:b:c:d:e",
+			"a::1",
+			"a:b:1@",
+			"/etc:a:1",
+			"a:..:1",
+			"a:b:..",
+			"a:b:1:../x",
+			"a..b:c:1",
+			".:b:1",
+			"a\\b:c:1",
+			"a:b:1@../jar",
+		] {
+			assert!(
+				name.parse::<ArtifactName>().is_err(),
+				"{name:?} is accepted"
+			);
+		}
+	}
+
+	#[test]
+	fn keeps_relative_paths_inside() {
+		assert_eq!(
+			safe_relative_path("minecraft/sounds/ambient/cave/cave1.ogg").unwrap(),
+			PathBuf::from("minecraft/sounds/ambient/cave/cave1.ogg")
+		);
+
+		for name in [
+			"",
+			"/etc/passwd",
+			"../../x",
+			"sounds/../../x",
+			"sounds/./x",
+			"sounds//x",
+			"sounds\\..\\x",
+			"C:/Windows",
+		] {
+			assert!(safe_relative_path(name).is_err(), "{name:?} is accepted");
+		}
+	}
 }
@@ -0,0 +1,658 @@
+use super::*;
+
+/// Placeholders substituted by launchers in manifest arguments
+pub const KNOWN_PLACEHOLDERS: &[&str] = &[
+	"auth_player_name",
+	"auth_session",
+	"auth_uuid",
+	"auth_access_token",
+	"auth_xuid",
+	"clientid",
+	"user_type",
+	"user_properties",
+	"version_name",
+	"version_type",
+	"game_directory",
+	"game_assets",
+	"assets_root",
+	"assets_index_name",
+	"library_directory",
+	"natives_directory",
+	"classpath",
+	"classpath_separator",
+	"launcher_name",
+	"launcher_version",
+	"resolution_width",
+	"resolution_height",
+	"quickPlayPath",
+	"quickPlaySingleplayer",
+	"quickPlayMultiplayer",
+	"quickPlayRealms",
+];
+
+const KNOWN_FEATURES: &[&str] = &[
+	features::DEMO_USER,
+	features::CUSTOM_RESOLUTION,
+	features::QUICK_PLAYS_SUPPORT,
+	features::QUICK_PLAY_SINGLEPLAYER,
+	features::QUICK_PLAY_MULTIPLAYER,
+	features::QUICK_PLAY_REALMS,
+];
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+	Error,
+	Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	/// JSON pointer to the offending entry
+	pub pointer: String,
+	pub message: String,
+}
+
+/// Names of all `${name}` placeholders in the value
+pub fn placeholders(value: &str) -> Vec<&str> {
+	let mut found = Vec::new();
+	let mut rest = value;
+
+	while let Some(start) = rest.find("${") {
+		rest = &rest[start + 2..];
+
+		let Some(end) = rest.find('}') else {
+			break;
+		};
+
+		found.push(&rest[..end]);
+		rest = &rest[end + 1..];
+	}
+
+	found
+}
+
+fn escape(token: &str) -> String {
+	token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Hex digest of exactly 40 characters
+pub(crate) fn is_sha1(value: &str) -> bool {
+	value.len() == 40 && value.bytes().all(|it| it.is_ascii_hexdigit())
+}
+
+/// Whether the rules allow the library on the OS with any architecture, e.g.
+/// twitch natives are disallowed on linux and have no classifier for it
+fn is_allowed_on(rules: &[Rule], os: &Os) -> bool {
+	[Arch::X64, Arch::X86, Arch::Arm64].into_iter().any(|arch| {
+		let ctx = RuleContext {
+			os: os.clone(),
+			arch,
+			os_version: None,
+			features: HashMap::new(),
+		};
+
+		Rule::unpack_all(rules, &ctx)
+	})
+}
+
+/// Keys of a rule besides its action, others are ignored when it is applied
+const RULE_CONDITIONS: &[&str] = &["os", "features"];
+
+const OS_CONDITIONS: &[&str] = &["name", "arch", "version"];
+
+#[derive(Debug, Clone, Copy)]
+enum Shape {
+	String,
+	Object,
+	Array,
+}
+
+impl Shape {
+	fn matches(&self, value: &Value) -> bool {
+		match self {
+			Shape::String => value.is_string(),
+			Shape::Object => value.is_object(),
+			Shape::Array => value.is_array(),
+		}
+	}
+
+	fn name(&self) -> &'static str {
+		match self {
+			Shape::String => "a string",
+			Shape::Object => "an object",
+			Shape::Array => "an array",
+		}
+	}
+}
+
+#[derive(Default)]
+struct Report {
+	diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+	fn push(&mut self, severity: Severity, pointer: impl Into<String>, message: impl Into<String>) {
+		self.diagnostics.push(Diagnostic {
+			severity,
+			pointer: pointer.into(),
+			message: message.into(),
+		})
+	}
+
+	fn sha1(&mut self, pointer: String, value: &str) {
+		if !is_sha1(value) {
+			self.push(Severity::Error, pointer, format!("Invalid sha1 `{value}`"));
+		}
+	}
+
+	fn expect(
+		&mut self,
+		object: &Map<String, Value>,
+		pointer: &str,
+		key: &str,
+		shape: Shape,
+		required: bool,
+	) {
+		let pointer = format!("{pointer}/{}", escape(key));
+
+		match object.get(key) {
+			None if required => self.push(Severity::Error, pointer, format!("Missing `{key}`")),
+			Some(value) if !shape.matches(value) => self.push(
+				Severity::Error,
+				pointer,
+				format!("`{key}` is not {}", shape.name()),
+			),
+			_ => {}
+		}
+	}
+
+	fn raw_rules(&mut self, pointer: &str, rules: Option<&Value>) {
+		let Some(rules) = rules else {
+			return;
+		};
+
+		let Some(rules) = rules.as_array() else {
+			self.push(Severity::Error, pointer, "Rules are not an array");
+			return;
+		};
+
+		for (index, rule) in rules.iter().enumerate() {
+			let pointer = format!("{pointer}/{index}");
+
+			let Some(rule) = rule.as_object() else {
+				self.push(Severity::Error, pointer, "Rule is not an object");
+				continue;
+			};
+
+			if !matches!(
+				rule.get("action").and_then(Value::as_str),
+				Some("allow" | "disallow")
+			) {
+				self.push(
+					Severity::Error,
+					format!("{pointer}/action"),
+					"Rule action is neither `allow` nor `disallow`",
+				);
+			}
+
+			for key in rule
+				.keys()
+				.filter(|it| *it != "action" && !RULE_CONDITIONS.contains(&it.as_str()))
+			{
+				self.push(
+					Severity::Warning,
+					format!("{pointer}/{}", escape(key)),
+					format!("Unsupported rule condition `{key}`, the rule applies regardless of it"),
+				);
+			}
+
+			if let Some(os) = rule.get("os").and_then(Value::as_object) {
+				for key in os.keys().filter(|it| !OS_CONDITIONS.contains(&it.as_str())) {
+					self.push(
+						Severity::Warning,
+						format!("{pointer}/os/{}", escape(key)),
+						format!("Unsupported os condition `{key}`"),
+					);
+				}
+			}
+		}
+	}
+
+	/// Checks the shape of the raw manifest, reading it as a [`Manifest`] only
+	/// tells that no variant matched
+	fn structure(&mut self, value: &Value) {
+		let Some(root) = value.as_object() else {
+			self.push(Severity::Error, "", "Manifest is not an object");
+			return;
+		};
+
+		let is_inherited = root.contains_key("inheritsFrom");
+
+		let mut fields = vec![
+			("id", Shape::String, true),
+			("mainClass", Shape::String, true),
+			("type", Shape::String, true),
+			("libraries", Shape::Array, !is_inherited),
+			("arguments", Shape::Object, false),
+			("minecraftArguments", Shape::String, false),
+		];
+
+		if is_inherited {
+			fields.push(("inheritsFrom", Shape::String, true));
+		} else {
+			fields.extend([
+				("assets", Shape::String, true),
+				("assetIndex", Shape::Object, true),
+				("downloads", Shape::Object, true),
+				("javaVersion", Shape::Object, true),
+			]);
+
+			if !root.contains_key("arguments") && !root.contains_key("minecraftArguments") {
+				self.push(
+					Severity::Error,
+					"/arguments",
+					"Missing `arguments` or `minecraftArguments`",
+				);
+			}
+		}
+
+		for (key, shape, required) in fields {
+			self.expect(root, "", key, shape, required);
+		}
+
+		if let Some(libraries) = root.get("libraries").and_then(Value::as_array) {
+			for (index, library) in libraries.iter().enumerate() {
+				let pointer = format!("/libraries/{index}");
+
+				let Some(library) = library.as_object() else {
+					self.push(Severity::Error, pointer, "Library is not an object");
+					continue;
+				};
+
+				self.expect(library, &pointer, "name", Shape::String, true);
+				self.raw_rules(&format!("{pointer}/rules"), library.get("rules"));
+			}
+		}
+
+		if let Some(arguments) = root.get("arguments").and_then(Value::as_object) {
+			for kind in ["game", "jvm"] {
+				let Some(args) = arguments.get(kind).and_then(Value::as_array) else {
+					continue;
+				};
+
+				for (index, arg) in args.iter().enumerate() {
+					if let Some(arg) = arg.as_object() {
+						self.raw_rules(
+							&format!("/arguments/{kind}/{index}/rules"),
+							arg.get("rules"),
+						);
+					}
+				}
+			}
+		}
+	}
+
+	fn main_class(&mut self, main_class: &str) {
+		if main_class.trim().is_empty() {
+			self.push(Severity::Error, "/mainClass", "Missing main class");
+		}
+	}
+
+	fn rules(&mut self, pointer: &str, rules: &[Rule]) {
+		for (index, rule) in rules.iter().enumerate() {
+			let pointer = format!("{pointer}/{index}");
+
+			match &rule.condition {
+				Some(RuleCondition::Features(features)) => {
+					for name in features.keys() {
+						if !KNOWN_FEATURES.contains(&name.as_str()) {
+							self.push(
+								Severity::Warning,
+								format!("{pointer}/features/{}", escape(name)),
+								format!("Unsupported feature `{name}`"),
+							);
+						}
+					}
+				}
+				Some(RuleCondition::Os {
+					version: Some(version),
+					..
+				}) if Regex::new(version).is_err() => {
+					self.push(
+						Severity::Error,
+						format!("{pointer}/os/version"),
+						format!("Invalid os version pattern `{version}`"),
+					);
+				}
+				_ => {}
+			}
+		}
+	}
+
+	fn argument_value(&mut self, pointer: String, value: &str) {
+		for name in placeholders(value) {
+			if !KNOWN_PLACEHOLDERS.contains(&name) {
+				self.push(
+					Severity::Warning,
+					pointer.clone(),
+					format!("Unknown placeholder `${{{name}}}`"),
+				);
+			}
+		}
+	}
+
+	fn arguments(&mut self, arguments: &ArgsContainer) {
+		match arguments {
+			ArgsContainer::Legacy(it) => {
+				self.argument_value("/minecraftArguments".into(), &it.arguments);
+			}
+			ArgsContainer::Modern(ModernArgs { arguments }) => {
+				for (kind, args) in [("game", &arguments.game), ("jvm", &arguments.jvm)] {
+					for (index, arg) in args.iter().enumerate() {
+						let pointer = format!("/arguments/{kind}/{index}");
+
+						match arg {
+							Argument::Constant(it) => self.argument_value(pointer, it),
+							Argument::Conditional { rules, value } => {
+								self.rules(&format!("{pointer}/rules"), rules);
+
+								match value {
+									ConditionalArgument::Single(it) => {
+										self.argument_value(format!("{pointer}/value"), it)
+									}
+									ConditionalArgument::List(its) => {
+										for (value_index, it) in its.iter().enumerate() {
+											self.argument_value(format!("{pointer}/value/{value_index}"), it)
+										}
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	fn artifact(&mut self, pointer: String, artifact: &Artifact) {
+		self.sha1(format!("{pointer}/sha1"), &artifact.sha1);
+
+		if let Some(url) = &artifact.url {
+			if !url.is_empty() && Url::parse(url).is_err() {
+				self.push(
+					Severity::Error,
+					format!("{pointer}/url"),
+					format!("Invalid url `{url}`"),
+				);
+			}
+		}
+	}
+
+	fn libraries(&mut self, libraries: &[Library]) {
+		for (index, library) in libraries.iter().enumerate() {
+			let pointer = format!("/libraries/{index}");
+
+			let duplicate = libraries[..index]
+				.iter()
+				.position(|it| it.name().is_same_artifact(library.name()));
+
+			if let Some(original) = duplicate {
+				self.push(
+					Severity::Warning,
+					format!("{pointer}/name"),
+					format!(
+						"Duplicated artifact {}, already declared at /libraries/{original}",
+						library.name()
+					),
+				);
+			}
+
+			match library {
+				Library::Common(it) => self.artifact(
+					format!("{pointer}/downloads/artifact"),
+					&it.downloads.artifact,
+				),
+				Library::Seminative(it) => {
+					self.rules(&format!("{pointer}/rules"), &it.rules);
+					self.artifact(
+						format!("{pointer}/downloads/artifact"),
+						&it.downloads.artifact,
+					);
+				}
+				Library::Native(it) => {
+					self.rules(&format!("{pointer}/rules"), &it.rules);
+
+					if let Some(artifact) = &it.downloads.artifact {
+						self.artifact(format!("{pointer}/downloads/artifact"), artifact);
+					}
+
+					for (classifier, artifact) in &it.downloads.classifiers {
+						self.artifact(
+							format!("{pointer}/downloads/classifiers/{}", escape(classifier)),
+							artifact,
+						);
+					}
+
+					for (os, classifier) in &it.natives.inner {
+						if !is_allowed_on(&it.rules, os) {
+							continue;
+						}
+
+						let os = format!("{os:?}").to_lowercase();
+						let has_classifier = [Arch::X64, Arch::X86].iter().any(|arch| {
+							it.downloads
+								.classifiers
+								.contains_key(&classifier.replace("${arch}", arch.bits()))
+						});
+
+						if !has_classifier {
+							self.push(
+								Severity::Error,
+								format!("{pointer}/natives/{os}"),
+								format!("Missing native classifier `{classifier}` for {os}"),
+							);
+						}
+					}
+				}
+				Library::Custom(it) => {
+					if let Some(sha1) = &it.sha1 {
+						self.sha1(format!("{pointer}/sha1"), sha1);
+					}
+
+					if it.url.is_none() {
+						self.push(
+							Severity::Warning,
+							pointer,
+							format!(
+								"Library {} has no downloads, it will be looked up in default repositories",
+								it.name
+							),
+						);
+					}
+				}
+			}
+		}
+	}
+}
+
+impl NativeManifest {
+	pub fn validate(&self) -> Vec<Diagnostic> {
+		let mut report = Report::default();
+
+		report.main_class(&self.main_class);
+		report.sha1("/downloads/client/sha1".into(), &self.downloads.client.sha1);
+		report.sha1("/assetIndex/sha1".into(), &self.asset_index.sha1);
+
+		if let Some(Logging { client, .. }) = &self.logging {
+			report.sha1("/logging/client/file/sha1".into(), &client.file.sha1);
+		}
+
+		report.arguments(&self.arguments);
+		report.libraries(&self.libraries);
+
+		report.diagnostics
+	}
+}
+
+impl InheritedManifest {
+	pub fn validate(&self) -> Vec<Diagnostic> {
+		let mut report = Report::default();
+
+		report.main_class(&self.main_class);
+
+		if let Some(arguments) = &self.arguments {
+			report.arguments(arguments);
+		}
+
+		report.libraries(&self.libraries);
+
+		report.diagnostics
+	}
+}
+
+impl Manifest {
+	pub fn validate(&self) -> Vec<Diagnostic> {
+		match self {
+			Manifest::Native(it) => it.validate(),
+			Manifest::Inherited(it) => it.validate(),
+		}
+	}
+
+	/// Validates the manifest JSON before reading it, so entries that make it
+	/// unreadable are reported with their pointers as well
+	pub fn validate_json(content: &str) -> Vec<Diagnostic> {
+		let mut report = Report::default();
+
+		let value: Value = match serde_json::from_str(content) {
+			Ok(value) => value,
+			Err(err) => {
+				report.push(Severity::Error, "", format!("Invalid JSON: {err}"));
+				return report.diagnostics;
+			}
+		};
+
+		report.structure(&value);
+
+		let is_readable = report
+			.diagnostics
+			.iter()
+			.all(|it| it.severity != Severity::Error);
+
+		if is_readable {
+			match serde_json::from_value::<Manifest>(value) {
+				Ok(manifest) => report.diagnostics.extend(manifest.validate()),
+				Err(err) => report.push(Severity::Error, "", format!("Invalid manifest: {err}")),
+			}
+		}
+
+		report.diagnostics
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn pointers(content: &str) -> Vec<(Severity, String)> {
+		Manifest::validate_json(content)
+			.into_iter()
+			.map(|it| (it.severity, it.pointer))
+			.collect()
+	}
+
+	#[test]
+	fn reports_unreadable_entries() {
+		let content = r#"{
+			"id": "fabric",
+			"inheritsFrom": "1.20",
+			"type": "release",
+			"libraries": [{ "url": "https://maven.fabricmc.net/" }]
+		}"#;
+
+		assert_eq!(
+			pointers(content),
+			[
+				(Severity::Error, "/mainClass".to_owned()),
+				(Severity::Error, "/libraries/0/name".to_owned()),
+			]
+		);
+	}
+
+	#[test]
+	fn reports_unknown_rule_conditions() {
+		let content = r#"{
+			"id": "fabric",
+			"inheritsFrom": "1.20",
+			"mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+			"type": "release",
+			"arguments": {
+				"jvm": [{ "rules": [{ "action": "allow", "platform": "linux" }], "value": "-Da" }]
+			}
+		}"#;
+
+		assert_eq!(
+			pointers(content),
+			[(
+				Severity::Warning,
+				"/arguments/jvm/0/rules/0/platform".to_owned()
+			)]
+		);
+	}
+
+	#[test]
+	fn skips_natives_of_disallowed_os() {
+		// Library of 1.7.10 to 1.12.2 with no linux classifier
+		let content = r#"{
+			"id": "1.12.2-forge",
+			"inheritsFrom": "1.12.2",
+			"mainClass": "net.minecraft.launchwrapper.Launch",
+			"type": "release",
+			"libraries": [{
+				"extract": { "exclude": ["META-INF/"] },
+				"name": "tv.twitch:twitch-platform:6.5",
+				"natives": {
+					"linux": "natives-linux",
+					"osx": "natives-osx",
+					"windows": "natives-windows-${arch}"
+				},
+				"rules": [
+					{ "action": "allow" },
+					{ "action": "disallow", "os": { "name": "linux" } }
+				],
+				"downloads": {
+					"classifiers": {
+						"natives-osx": {
+							"path": "tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-osx.jar",
+							"sha1": "5f9d1ee26257b3a33f0ca06fed335ef462af659f",
+							"size": 455359,
+							"url": "https://libraries.minecraft.net/tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-osx.jar"
+						},
+						"natives-windows-32": {
+							"path": "tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-windows-32.jar",
+							"sha1": "206c4ccaecdbcfd2a1631150c69a97bbc9c20c11",
+							"size": 474225,
+							"url": "https://libraries.minecraft.net/tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-windows-32.jar"
+						},
+						"natives-windows-64": {
+							"path": "tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-windows-64.jar",
+							"sha1": "9fdd0fd5aed0817063dcf95b69349a171f447ebd",
+							"size": 580098,
+							"url": "https://libraries.minecraft.net/tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-windows-64.jar"
+						}
+					}
+				}
+			}]
+		}"#;
+
+		assert_eq!(pointers(content), []);
+
+		let disallowed_osx = content.replace(r#""name": "linux""#, r#""name": "osx""#);
+
+		assert_eq!(
+			pointers(&disallowed_osx),
+			[(Severity::Error, "/libraries/0/natives/linux".to_owned())]
+		);
+	}
+}
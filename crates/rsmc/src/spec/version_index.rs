@@ -0,0 +1,47 @@
+use {
+	serde::{
+		Deserialize,
+		Serialize,
+	},
+	url::Url,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatestVersions {
+	pub release: String,
+	pub snapshot: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionIndexEntry {
+	pub id: String,
+	#[serde(rename = "type")]
+	pub version_type: String,
+	pub url: Url,
+	pub time: String,
+	pub release_time: String,
+	pub sha1: String,
+	pub compliance_level: u32,
+}
+
+/// Mojang `version_manifest_v2.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionIndex {
+	pub latest: LatestVersions,
+	pub versions: Vec<VersionIndexEntry>,
+}
+
+impl VersionIndex {
+	pub fn find(&self, id: &str) -> Option<&VersionIndexEntry> {
+		self.versions.iter().find(|it| it.id == id)
+	}
+
+	pub fn latest_release(&self) -> Option<&VersionIndexEntry> {
+		self.find(&self.latest.release)
+	}
+
+	pub fn latest_snapshot(&self) -> Option<&VersionIndexEntry> {
+		self.find(&self.latest.snapshot)
+	}
+}
@@ -38,14 +38,15 @@ export function useLauncher() {
 				versionId: instance.version.vid,
 				provider: instance.version.provider === 'local' ? 'mojang' : instance.version.provider,
 				logbackId: 'unknown',
+				alloc: instance.alloc,
 				vars: {
 					auth_player_name: nickname,
 					auth_uuid: 'bd983a9c-0622-42dc-a0c2-47c71bd4f21b',
 					auth_access_token: 'null',
 					game_directory: instance.path,
 					user_type: 'msa',
-					width: instance.width?.toString(10) ?? '1280',
-					height: instance.height?.toString(10) ?? '720',
+					resolution_width: instance.width?.toString(10) ?? '1280',
+					resolution_height: instance.height?.toString(10) ?? '720',
 				},
 			})
 				.catch(setError)
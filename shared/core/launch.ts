@@ -6,6 +6,8 @@ export interface LaunchOptions {
 	provider: string
 	root: string
 	vars: Record<string, string>
+	/** Maximum heap size in megabytes */
+	alloc?: number
 }
 
 export async function launch(options: LaunchOptions) {
@@ -22,8 +24,8 @@ export async function launch(options: LaunchOptions) {
 
 	// eslint-disable-next-line no-lone-blocks
 	{
-		const { versionId: id, root, vars } = options
+		const { versionId: id, root, vars, alloc } = options
 
-		await invoke('launch', { id, root, vars })
+		await invoke('launch', { id, root, vars, options: { jvm: { maxMemory: alloc } } })
 	}
 }
@@ -54,7 +54,18 @@ fn main() {
 
 			Ok(())
 		})
-		.invoke_handler(tauri::generate_handler![ipc::lookup_versions, ipc::launch,])
+		.invoke_handler(tauri::generate_handler![
+			ipc::lookup_versions,
+			ipc::lookup_remote_versions,
+			ipc::mojang_prepare,
+			ipc::mojang_prepare_server,
+			ipc::validate_version,
+			ipc::export_launch_script,
+			ipc::launch,
+			ipc::stop_game,
+			ipc::launch_server,
+			ipc::server_command,
+		])
 		.run(tauri::generate_context!())
 		.expect("error while running tauri application");
 }